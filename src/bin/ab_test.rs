@@ -0,0 +1,128 @@
+extern crate my_bot;
+
+use my_bot::engine::ab_test::AbTest;
+use my_bot::engine::ab_test::PairResult;
+use my_bot::engine::ab_test::Verdict;
+use my_bot::engine::cli::Cli;
+use my_bot::engine::runner::Match;
+use my_bot::engine::runner::MatchResult;
+use my_bot::engine::runner::MatchSettings;
+use my_bot::engine::tournament;
+use std::env;
+use std::process::exit;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const USAGE: &str = "Usage: ab_test [--seed N] [--sizes 32,40,...] [--min-pairs N] [--max-pairs N] [--margin P] [--alpha P] [--beta P] [--turn-timeout MS] [--init-timeout MS] \"<bot command A>\" \"<bot command B>\"";
+const CLI: Cli = Cli { usage: USAGE };
+
+// Exit code when B turns out worse, for scripts that gate on it
+const REGRESSION_EXIT_CODE: i32 = 2;
+
+fn play(settings: MatchSettings, commands: &[String]) -> MatchResult {
+    match Match::new(settings, commands) {
+        Ok(mut game) => game.run(),
+        Err(error) => CLI.fail(&format!("Error: {}", error)),
+    }
+}
+
+// Plays build A, the baseline, against build B on one seed after another, each seed from both
+// sides, until the sequential test can tell whether B is a regression or the pairs run out. Sizes take
+// turns so every ladder size gets played. Bots are commands or "builtin:<strategy>" like for
+// match_runner.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut first_seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let mut sizes: Vec<usize> = tournament::LADDER_SIZES.to_vec();
+    let mut min_pairs: usize = 10;
+    let mut max_pairs: usize = 200;
+    let mut margin = 0.05;
+    let mut alpha = 0.05;
+    let mut beta = 0.05;
+    let mut turn_timeout: Option<Duration> = None;
+    let mut init_timeout: Option<Duration> = None;
+    let mut commands: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = args.get(i + 1);
+        match flag {
+            "--seed" => first_seed = CLI.parse_number(flag, value),
+            "--sizes" => sizes = CLI.parse_list(flag, value),
+            "--min-pairs" => min_pairs = CLI.parse_number(flag, value),
+            "--max-pairs" => max_pairs = CLI.parse_number(flag, value),
+            "--margin" => margin = CLI.parse_number(flag, value),
+            "--alpha" => alpha = CLI.parse_number(flag, value),
+            "--beta" => beta = CLI.parse_number(flag, value),
+            "--turn-timeout" => turn_timeout = Some(Duration::from_millis(CLI.parse_number(flag, value))),
+            "--init-timeout" => init_timeout = Some(Duration::from_millis(CLI.parse_number(flag, value))),
+            _ if flag.starts_with("--") => CLI.fail(&format!("Unknown option {}", flag)),
+            _ => {
+                commands.push(args[i].clone());
+                i += 1;
+                continue;
+            },
+        }
+        i += 2;
+    }
+
+    if commands.len() != 2 {
+        CLI.fail("Expected exactly two bot commands, A and B");
+    }
+    if sizes.is_empty() {
+        CLI.fail("--sizes needs at least one size");
+    }
+    if [margin, alpha, beta].iter().any(|value| *value <= 0.0 || *value >= 0.5) {
+        CLI.fail("--margin, --alpha and --beta must be between 0 and 0.5");
+    }
+    let swapped = vec![commands[1].clone(), commands[0].clone()];
+
+    let mut test = AbTest::new(margin, alpha, beta, min_pairs);
+    let (lower, upper) = test.get_llr_bounds();
+    for pair in 0..max_pairs {
+        let seed = first_seed + pair as u64;
+        let size = sizes[pair % sizes.len()];
+        let get_settings = || {
+            let mut settings = MatchSettings::new(seed, size);
+            if let Some(turn_timeout) = turn_timeout {
+                settings.turn_timeout = turn_timeout;
+            }
+            if let Some(init_timeout) = init_timeout {
+                settings.init_timeout = init_timeout;
+            }
+            settings
+        };
+        let first = play(get_settings(), &commands);
+        let second = play(get_settings(), &swapped);
+        let result = PairResult::new(seed, size, &first, &second);
+        println!("Pair {} ({}x{} seed {}): B won {} of 2, halite B - A {:+.0}", pair + 1, size, size, seed, result.b_wins, result.halite_difference);
+        test.record(result);
+
+        let win_rate = test.get_win_rate();
+        println!("  B win rate {:.1}% [{:.1}%, {:.1}%], LLR {:.2} ({:.2}, {:.2})",
+            100.0 * win_rate.mean, 100.0 * win_rate.low, 100.0 * win_rate.high, test.get_llr(), lower, upper);
+        if test.get_verdict().is_some() {
+            break;
+        }
+    }
+
+    let win_rate = test.get_win_rate();
+    let difference = test.get_halite_difference();
+    println!();
+    println!("A: {}", commands[0]);
+    println!("B: {}", commands[1]);
+    println!("{} games on {} seeds", test.get_games(), test.pairs.len());
+    println!("B win rate {:.1}%, 95% confidence interval [{:.1}%, {:.1}%]", 100.0 * win_rate.mean, 100.0 * win_rate.low, 100.0 * win_rate.high);
+    println!("B halite - A halite {:+.0}, 95% confidence interval [{:+.0}, {:+.0}]", difference.mean, difference.low, difference.high);
+    match test.get_verdict() {
+        Some(Verdict::NotWorse) => println!("Sequential test: B is no worse than A (LLR {:.2} >= {:.2})", test.get_llr(), upper),
+        Some(Verdict::Worse) => println!("Sequential test: B is worse (LLR {:.2} <= {:.2})", test.get_llr(), lower),
+        None => println!("Sequential test: inconclusive after {} pairs (LLR {:.2})", test.pairs.len(), test.get_llr()),
+    }
+    if test.is_significant_regression() {
+        println!("REGRESSION: B is significantly worse than A");
+        exit(REGRESSION_EXIT_CODE);
+    }
+}
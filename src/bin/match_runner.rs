@@ -0,0 +1,71 @@
+extern crate my_bot;
+
+use my_bot::engine::cli::Cli;
+use my_bot::engine::runner::Match;
+use my_bot::engine::runner::MatchSettings;
+use std::env;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const USAGE: &str = "Usage: match_runner [--seed N] [--size N] [--turns N] [--turn-timeout MS] [--init-timeout MS] [--replay PATH] \"<bot command>\" \"<bot command>\" [\"<bot command>\" \"<bot command>\"]";
+const CLI: Cli = Cli { usage: USAGE };
+
+// Plays one game between bot executables, each started with "sh -c <command>", or builtin strategies
+// given as "builtin:<strategy> [key=value ...]", and prints the results
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let mut settings = MatchSettings::new(seed, 32);
+    let mut commands: Vec<String> = Vec::new();
+    let mut replay_path: Option<String> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = args.get(i + 1);
+        match flag {
+            "--seed" => settings.seed = CLI.parse_number(flag, value),
+            "--size" => settings.size = CLI.parse_number(flag, value),
+            "--turns" => settings.max_turns = Some(CLI.parse_number(flag, value)),
+            "--turn-timeout" => settings.turn_timeout = Duration::from_millis(CLI.parse_number(flag, value)),
+            "--init-timeout" => settings.init_timeout = Duration::from_millis(CLI.parse_number(flag, value)),
+            "--replay" => match value {
+                Some(path) => replay_path = Some(path.clone()),
+                None => CLI.fail("--replay expects a path"),
+            },
+            _ if flag.starts_with("--") => CLI.fail(&format!("Unknown option {}", flag)),
+            _ => {
+                commands.push(args[i].clone());
+                i += 1;
+                continue;
+            },
+        }
+        i += 2;
+    }
+
+    settings.record_replay = replay_path.is_some();
+    let (seed, size) = (settings.seed, settings.size);
+    let mut game = match Match::new(settings, &commands) {
+        Ok(game) => game,
+        Err(error) => CLI.fail(&format!("Error: {}", error)),
+    };
+    let result = game.run();
+
+    println!("Map seed {} size {}x{}, {} turns", seed, size, size, result.turns);
+    let mut players: Vec<_> = result.players.iter().collect();
+    players.sort_by_key(|player| player.rank);
+    for player in players {
+        let status = match &player.elimination {
+            Some((turn_number, reason)) => format!(", out on turn {}: {}", turn_number, reason),
+            None => String::new(),
+        };
+        println!("#{} player {} {} ({}): {} halite{}", player.rank, player.player_id.0, player.name, player.command, player.halite, status);
+    }
+    if let Some(path) = replay_path {
+        match game.write_replay(&path) {
+            Ok(()) => println!("Replay written to {}", path),
+            Err(error) => CLI.fail(&format!("Error: {}", error)),
+        }
+    }
+}
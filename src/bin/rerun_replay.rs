@@ -0,0 +1,85 @@
+extern crate my_bot;
+
+use my_bot::engine::cli::Cli;
+use my_bot::hlt::PlayerId;
+use my_bot::replay::reader::Replay;
+use my_bot::replay::transcript;
+use std::env;
+use std::fs;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: rerun_replay [--player N] [--transcript PATH] [--turn-timeout MS] [--init-timeout MS] <replay.hlt> [\"<bot command>\"]";
+const CLI: Cli = Cli { usage: USAGE };
+
+// Rebuilds what one player of a logged game got from the server. The transcript can be written
+// out to pipe into a bot by hand, or played to a bot right away to see on which turns its
+// commands differ from the logged ones.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut player_id = PlayerId(0);
+    let mut transcript_path: Option<String> = None;
+    let mut init_timeout = Duration::from_secs(30);
+    let mut turn_timeout = Duration::from_secs(10);
+    let mut positional: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = args.get(i + 1);
+        match flag {
+            "--player" => player_id = PlayerId(CLI.parse_number(flag, value)),
+            "--transcript" => match value {
+                Some(path) => transcript_path = Some(path.clone()),
+                None => CLI.fail("--transcript expects a path"),
+            },
+            "--turn-timeout" => turn_timeout = Duration::from_millis(CLI.parse_number(flag, value)),
+            "--init-timeout" => init_timeout = Duration::from_millis(CLI.parse_number(flag, value)),
+            _ if flag.starts_with("--") => CLI.fail(&format!("Unknown option {}", flag)),
+            _ => {
+                positional.push(args[i].clone());
+                i += 1;
+                continue;
+            },
+        }
+        i += 2;
+    }
+
+    let (replay_path, command) = match positional.as_slice() {
+        [replay_path] => (replay_path, None),
+        [replay_path, command] => (replay_path, Some(command)),
+        _ => CLI.fail("Expected a replay and at most one bot command"),
+    };
+    if command.is_none() && transcript_path.is_none() {
+        CLI.fail("Nothing to do without a bot command or --transcript");
+    }
+    let replay = match Replay::read(replay_path) {
+        Ok(replay) => replay,
+        Err(error) => CLI.fail(&format!("Error: {}", error)),
+    };
+    if player_id.0 >= replay.initial_players.len() {
+        CLI.fail(&format!("Error: the replay only has {} players", replay.initial_players.len()));
+    }
+
+    if let Some(path) = transcript_path {
+        let mut text = transcript::get_transcript(&replay, player_id).join("\n");
+        text.push('\n');
+        if let Err(error) = fs::write(&path, text) {
+            CLI.fail(&format!("Error: can't write {}: {}", path, error));
+        }
+        println!("Transcript of player {} written to {}", player_id.0, path);
+    }
+
+    if let Some(command) = command {
+        let comparisons = match transcript::rerun(&replay, player_id, command, init_timeout, turn_timeout) {
+            Ok(comparisons) => comparisons,
+            Err(error) => CLI.fail(&format!("Error: {}", error)),
+        };
+        for comparison in comparisons.iter().filter(|comparison| !comparison.is_same()) {
+            println!("Turn {}:", comparison.turn_number);
+            println!("  logged: {}", comparison.logged.join(", "));
+            println!("  rerun:  {}", comparison.rerun.join(", "));
+        }
+        let same = comparisons.iter().filter(|comparison| comparison.is_same()).count();
+        println!("Player {} ({}): {} of {} turns match", player_id.0, replay.player_names[player_id.0], same, comparisons.len());
+    }
+}
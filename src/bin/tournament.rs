@@ -0,0 +1,119 @@
+extern crate my_bot;
+extern crate serde_json;
+
+use my_bot::engine::cli::Cli;
+use my_bot::engine::runner::Match;
+use my_bot::engine::runner::MatchSettings;
+use my_bot::engine::tournament::Tournament;
+use my_bot::engine::tournament;
+use std::env;
+use std::fs;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const USAGE: &str = "Usage: tournament [--seed N] [--seeds N] [--sizes 32,40,...] [--players 2,4] [--turn-timeout MS] [--init-timeout MS] [--json PATH] [--csv PATH] \"<bot command>\" \"<bot command>\" ...";
+const CLI: Cli = Cli { usage: USAGE };
+
+fn write_report(path: &str, contents: &str) {
+    if let Err(error) = fs::write(path, contents) {
+        CLI.fail(&format!("Error: can't write {}: {}", path, error));
+    }
+    println!("Report written to {}", path);
+}
+
+// Plays every entrant against every other on each map size and player count, on the same seeds
+// and from every seat, and rates them. Entrants are bot commands or "builtin:<strategy>" like
+// for match_runner.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut first_seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let mut seeds = 1;
+    let mut sizes: Vec<usize> = tournament::LADDER_SIZES.to_vec();
+    let mut player_counts: Vec<usize> = vec![2, 4];
+    let mut turn_timeout: Option<Duration> = None;
+    let mut init_timeout: Option<Duration> = None;
+    let mut json_path: Option<String> = None;
+    let mut csv_path: Option<String> = None;
+    let mut entrants: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = args.get(i + 1);
+        match flag {
+            "--seed" => first_seed = CLI.parse_number(flag, value),
+            "--seeds" => seeds = CLI.parse_number(flag, value),
+            "--sizes" => sizes = CLI.parse_list(flag, value),
+            "--players" => player_counts = CLI.parse_list(flag, value),
+            "--turn-timeout" => turn_timeout = Some(Duration::from_millis(CLI.parse_number(flag, value))),
+            "--init-timeout" => init_timeout = Some(Duration::from_millis(CLI.parse_number(flag, value))),
+            "--json" | "--csv" => {
+                let path = match value {
+                    Some(path) => Some(path.clone()),
+                    None => CLI.fail(&format!("{} expects a path", flag)),
+                };
+                if flag == "--json" { json_path = path } else { csv_path = path }
+            },
+            _ if flag.starts_with("--") => CLI.fail(&format!("Unknown option {}", flag)),
+            _ => {
+                entrants.push(args[i].clone());
+                i += 1;
+                continue;
+            },
+        }
+        i += 2;
+    }
+
+    if entrants.len() < 2 {
+        CLI.fail("A tournament needs at least 2 entrants");
+    }
+    if player_counts.iter().any(|num_players| *num_players != 2 && *num_players != 4) {
+        CLI.fail("Games are for 2 or 4 players");
+    }
+
+    let schedule = tournament::get_schedule(entrants.len(), &sizes, &player_counts, first_seed, seeds);
+    let mut tournament = Tournament::new(&entrants);
+    for (index, pairing) in schedule.iter().enumerate() {
+        let mut settings = MatchSettings::new(pairing.seed, pairing.size);
+        if let Some(turn_timeout) = turn_timeout {
+            settings.turn_timeout = turn_timeout;
+        }
+        if let Some(init_timeout) = init_timeout {
+            settings.init_timeout = init_timeout;
+        }
+        let commands: Vec<String> = pairing.seats.iter().map(|&entrant| entrants[entrant].clone()).collect();
+        let mut game = match Match::new(settings, &commands) {
+            Ok(game) => game,
+            Err(error) => CLI.fail(&format!("Error: {}", error)),
+        };
+        let result = game.run();
+        tournament.record(pairing, &result);
+
+        let mut players: Vec<_> = result.players.iter().collect();
+        players.sort_by_key(|player| player.rank);
+        let placings: Vec<String> = players.iter()
+            .map(|player| format!("#{} entrant {} ({} halite)", player.rank, pairing.seats[player.player_id.0], player.halite))
+            .collect();
+        println!("[{}/{}] {}p {}x{} seed {}: {}", index + 1, schedule.len(), pairing.seats.len(), pairing.size, pairing.size, pairing.seed, placings.join(", "));
+    }
+
+    println!();
+    for entrant in tournament.get_standings() {
+        println!("{:7.1}  entrant {} {} ({})", tournament.ratings[entrant], entrant, tournament.names[entrant], tournament.entrants[entrant]);
+    }
+    println!();
+    for entry in tournament.get_configuration_stats() {
+        println!(
+            "{}p {}x{} entrant {}: won {} of {} ({:.0}%), mean rank {:.2}, mean halite {:.0}",
+            entry.num_players, entry.size, entry.size, entry.entrant, entry.wins, entry.games,
+            100.0 * entry.get_win_rate(), entry.get_mean_rank(), entry.get_mean_halite());
+    }
+
+    if let Some(path) = json_path {
+        write_report(&path, &serde_json::to_string_pretty(&tournament.to_json()).unwrap());
+    }
+    if let Some(path) = csv_path {
+        write_report(&path, &tournament.to_csv());
+    }
+}
@@ -0,0 +1,276 @@
+use engine::runner::MatchResult;
+
+// Normal quantile for the two sided 95% confidence intervals
+const Z_95: f64 = 1.96;
+// Pairs added to each of the three pair outcomes when estimating their spread, so the likelihood
+// ratio stays finite and sane while every pair so far had the same outcome
+const PSEUDO_PAIRS: f64 = 0.5;
+
+// Build B against build A on one seed, played once from each side of the map. Scores are B's.
+pub struct PairResult {
+    pub seed: u64,
+    pub size: usize,
+    pub b_wins: usize,
+    // B's halite minus A's, averaged over both games
+    pub halite_difference: f64,
+}
+
+impl PairResult {
+    // The first game has A as player 0, the second has B as player 0
+    pub fn new(seed: u64, size: usize, first: &MatchResult, second: &MatchResult) -> PairResult {
+        let get = |result: &MatchResult, player_id: usize| {
+            let player = result.players.iter().find(|player| player.player_id.0 == player_id).unwrap();
+            (player.rank, player.halite as f64)
+        };
+        let (a_first, b_first) = (get(first, 0), get(first, 1));
+        let (b_second, a_second) = (get(second, 0), get(second, 1));
+        let b_wins = (b_first.0 < a_first.0) as usize + (b_second.0 < a_second.0) as usize;
+        let halite_difference = ((b_first.1 - a_first.1) + (b_second.1 - a_second.1)) / 2.0;
+        PairResult { seed, size, b_wins, halite_difference }
+    }
+
+    pub fn get_score(&self) -> f64 {
+        self.b_wins as f64 / 2.0
+    }
+}
+
+pub struct Estimate {
+    pub mean: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+fn get_mean_and_variance(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n.max(1.0);
+    let variance = if values.len() > 1 {
+        values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    } else {
+        f64::INFINITY
+    };
+    (mean, variance)
+}
+
+// The mean with its 95% confidence interval, from the normal approximation
+fn estimate(values: &[f64]) -> Estimate {
+    let (mean, variance) = get_mean_and_variance(values);
+    let margin = Z_95 * (variance / values.len().max(1) as f64).sqrt();
+    Estimate { mean, low: mean - margin, high: mean + margin }
+}
+
+// Wilson score interval for a share of successes, which unlike the normal approximation stays
+// inside [0, 1] and doesn't shrink to nothing when every trial went the same way. Trials that
+// score in between count as part of a success, which only makes the interval wider than it needs to be.
+fn estimate_share(successes: f64, trials: usize) -> Estimate {
+    if trials == 0 {
+        return Estimate { mean: 0.5, low: 0.0, high: 1.0 };
+    }
+    let n = trials as f64;
+    let share = successes / n;
+    let z2 = Z_95 * Z_95;
+    let center = (share + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = Z_95 / (1.0 + z2 / n) * (share * (1.0 - share) / n + z2 / (4.0 * n * n)).sqrt();
+    Estimate { mean: share, low: (center - margin).max(0.0), high: (center + margin).min(1.0) }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    NotWorse,
+    Worse,
+}
+
+// Compares two builds on paired seeds. Pairs take out most of the luck of the map and the side,
+// so the pair is the unit for the statistics: a pair score of 1 is B winning both games.
+//
+// Testing stops with a generalized sequential probability ratio test between B's expected pair
+// score being 0.5, as good as A, and 0.5 - margin, which needs far fewer games than a fixed count
+// when B is clearly ahead or behind. Two identical builds get called a regression at most alpha of
+// the time, and a build that is worse by the margin gets through at most beta of the time. Only
+// the test decides; the confidence intervals are for reading the size of the difference.
+pub struct AbTest {
+    pub margin: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    pub min_pairs: usize,
+    pub pairs: Vec<PairResult>,
+}
+
+impl AbTest {
+    pub fn new(margin: f64, alpha: f64, beta: f64, min_pairs: usize) -> AbTest {
+        AbTest { margin, alpha, beta, min_pairs, pairs: Vec::new() }
+    }
+
+    pub fn record(&mut self, pair: PairResult) {
+        self.pairs.push(pair);
+    }
+
+    pub fn get_games(&self) -> usize {
+        2 * self.pairs.len()
+    }
+
+    // B's mean pair score, which is also its share of the games it won. The two games of a pair
+    // aren't independent, so the interval counts pairs rather than games.
+    pub fn get_win_rate(&self) -> Estimate {
+        let score: f64 = self.pairs.iter().map(|pair| pair.get_score()).sum();
+        estimate_share(score, self.pairs.len())
+    }
+
+    pub fn get_halite_difference(&self) -> Estimate {
+        let differences: Vec<f64> = self.pairs.iter().map(|pair| pair.halite_difference).collect();
+        estimate(&differences)
+    }
+
+    // Log likelihood ratio of B being as good as A over B being worse. A pair scores 0, 0.5 or 1,
+    // and the mean and variance come from how often each of those happened.
+    pub fn get_llr(&self) -> f64 {
+        if self.pairs.len() < 2 {
+            return 0.0;
+        }
+        let mut counts = [PSEUDO_PAIRS; 3];
+        for pair in &self.pairs {
+            counts[pair.b_wins.min(2)] += 1.0;
+        }
+        let total: f64 = counts.iter().sum();
+        let scores = [0.0, 0.5, 1.0];
+        let mean: f64 = counts.iter().zip(&scores).map(|(count, score)| count * score).sum::<f64>() / total;
+        let variance: f64 = counts.iter().zip(&scores).map(|(count, score)| count * (score - mean).powi(2)).sum::<f64>() / total;
+        let (worse, same) = (0.5 - self.margin, 0.5);
+        self.pairs.len() as f64 * (same - worse) * (mean - (worse + same) / 2.0) / variance
+    }
+
+    // Testing goes on while the ratio stays between these
+    pub fn get_llr_bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    // Whether enough pairs have been played to call it
+    pub fn get_verdict(&self) -> Option<Verdict> {
+        if self.pairs.len() < self.min_pairs {
+            return None;
+        }
+        let llr = self.get_llr();
+        let (lower, upper) = self.get_llr_bounds();
+        if llr >= upper {
+            Some(Verdict::NotWorse)
+        } else if llr <= lower {
+            Some(Verdict::Worse)
+        } else {
+            None
+        }
+    }
+
+    // The sequential test settled on B being worse
+    pub fn is_significant_regression(&self) -> bool {
+        self.get_verdict() == Some(Verdict::Worse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+    use super::estimate_share;
+    use super::AbTest;
+    use super::PairResult;
+    use super::Verdict;
+
+    // A test with the given number of pairs where B won both, one and none of the games
+    fn get_test(both: usize, one: usize, none: usize) -> AbTest {
+        let mut test = AbTest::new(0.05, 0.05, 0.05, 10);
+        let outcomes = vec![2; both].into_iter().chain(vec![1; one]).chain(vec![0; none]);
+        for (seed, b_wins) in outcomes.enumerate() {
+            test.record(PairResult { seed: seed as u64, size: 32, b_wins, halite_difference: 0.0 });
+        }
+        test
+    }
+
+    #[test]
+    fn wilson_interval_stays_inside_the_unit_range() {
+        let none = estimate_share(0.0, 20);
+        assert_eq!(none.mean, 0.0);
+        assert_eq!(none.low, 0.0);
+        assert!((none.high - 0.1611).abs() < 1e-3);
+
+        let half = estimate_share(10.0, 20);
+        assert!((half.low - 0.2993).abs() < 1e-3);
+        assert!((half.high - 0.7007).abs() < 1e-3);
+    }
+
+    #[test]
+    fn likelihood_ratio_stays_sane_when_every_pair_agrees() {
+        let lost = get_test(0, 0, 10);
+        let llr = lost.get_llr();
+        let (lower, upper) = lost.get_llr_bounds();
+        assert!(llr < lower && llr > -20.0, "llr {}", llr);
+        let llr = get_test(10, 0, 0).get_llr();
+        assert!(llr > upper && llr < 20.0, "llr {}", llr);
+    }
+
+    #[test]
+    fn even_split_leans_towards_no_regression() {
+        // 20 pairs plus half a pair of each outcome: mean 0.5, variance 2.75 / 21.5
+        let expected = 20.0 * 0.05 * 0.025 / (2.75 / 21.5);
+        assert!((get_test(5, 10, 5).get_llr() - expected).abs() < 1e-9);
+        assert!(get_test(4, 10, 6).get_llr() < 0.0);
+    }
+
+    #[test]
+    fn win_rate_interval_counts_pairs() {
+        let estimate = get_test(5, 10, 5).get_win_rate();
+        assert_eq!(estimate.mean, 0.5);
+        let games = estimate_share(20.0, 40);
+        assert!(estimate.low < games.low && estimate.high > games.high);
+        assert!((estimate.low - 0.2993).abs() < 1e-3);
+    }
+
+    // Share of A/B runs over at most max_pairs pairs that end up flagged as a regression, when B
+    // wins each game with the given chance
+    fn get_regression_rate(win_chance: f64, runs: usize, max_pairs: usize) -> f64 {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut regressions = 0;
+        for _ in 0..runs {
+            let mut test = AbTest::new(0.05, 0.05, 0.05, 10);
+            for seed in 0..max_pairs {
+                let b_wins = rng.gen_bool(win_chance) as usize + rng.gen_bool(win_chance) as usize;
+                test.record(PairResult { seed: seed as u64, size: 32, b_wins, halite_difference: 0.0 });
+                if test.get_verdict().is_some() {
+                    break;
+                }
+            }
+            regressions += test.is_significant_regression() as usize;
+        }
+        regressions as f64 / runs as f64
+    }
+
+    #[test]
+    fn identical_builds_are_rarely_called_a_regression() {
+        let rate = get_regression_rate(0.5, 500, 200);
+        assert!(rate <= 0.05, "{}", rate);
+    }
+
+    #[test]
+    fn clear_regressions_get_caught() {
+        let rate = get_regression_rate(0.4, 200, 200);
+        assert!(rate >= 0.9, "{}", rate);
+    }
+
+    #[test]
+    fn verdict_waits_for_the_minimum_number_of_pairs() {
+        assert_eq!(get_test(0, 0, 9).get_verdict(), None);
+        assert_eq!(get_test(0, 0, 10).get_verdict(), Some(Verdict::Worse));
+        assert_eq!(get_test(10, 0, 0).get_verdict(), Some(Verdict::NotWorse));
+        assert_eq!(get_test(4, 4, 4).get_verdict(), None);
+    }
+
+    #[test]
+    fn regressions_are_up_to_the_sequential_test() {
+        assert!(get_test(0, 0, 10).is_significant_regression());
+        // Way under half, but too few pairs for the test to call it
+        assert!(get_test(0, 2, 6).get_win_rate().high < 0.5);
+        assert!(!get_test(0, 2, 6).is_significant_regression());
+        assert!(!get_test(0, 1, 1).is_significant_regression());
+        assert!(!get_test(3, 3, 3).is_significant_regression());
+        assert!(!get_test(10, 0, 0).is_significant_regression());
+    }
+}
@@ -0,0 +1,30 @@
+use std::process::exit;
+use std::str::FromStr;
+
+// Command line handling shared by the tools: bad arguments print the message and the tool's usage
+pub struct Cli {
+    pub usage: &'static str,
+}
+
+impl Cli {
+    pub fn fail(&self, message: &str) -> ! {
+        eprintln!("{}", message);
+        eprintln!("{}", self.usage);
+        exit(1);
+    }
+
+    pub fn parse_number<T: FromStr>(&self, flag: &str, value: Option<&String>) -> T {
+        match value.map(|value| value.parse()) {
+            Some(Ok(number)) => number,
+            _ => self.fail(&format!("{} expects a number", flag)),
+        }
+    }
+
+    pub fn parse_list(&self, flag: &str, value: Option<&String>) -> Vec<usize> {
+        let numbers: Option<Result<Vec<usize>, _>> = value.map(|value| value.split(',').map(|number| number.trim().parse()).collect());
+        match numbers {
+            Some(Ok(numbers)) => numbers,
+            _ => self.fail(&format!("{} expects a comma separated list of numbers", flag)),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use hlt::command::Command;
+use hlt::direction::Direction;
+use hlt::ShipId;
+
+pub enum EngineCommand {
+    Spawn,
+    Construct(ShipId),
+    Move(ShipId, Direction),
+}
+
+impl EngineCommand {
+    pub fn get_ship_id(&self) -> Option<ShipId> {
+        match self {
+            EngineCommand::Spawn => None,
+            EngineCommand::Construct(ship_id) => Some(*ship_id),
+            EngineCommand::Move(ship_id, _) => Some(*ship_id),
+        }
+    }
+
+    pub fn to_command(&self) -> Command {
+        match self {
+            EngineCommand::Spawn => Command::spawn_ship(),
+            EngineCommand::Construct(ship_id) => Command::transform_ship_into_dropoff_site(*ship_id),
+            EngineCommand::Move(ship_id, direction) => Command::move_ship(*ship_id, *direction),
+        }
+    }
+
+    // Reads back what the bots send: "g", "c <ship id>" or "m <ship id> <direction>"
+    pub fn parse(command: &Command) -> Result<EngineCommand, String> {
+        let tokens: Vec<&str> = command.0.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["g"] => Ok(EngineCommand::Spawn),
+            ["c", ship_id] => Ok(EngineCommand::Construct(EngineCommand::parse_ship_id(ship_id)?)),
+            ["m", ship_id, direction] => {
+                let ship_id = EngineCommand::parse_ship_id(ship_id)?;
+                let mut chars = direction.chars();
+                match (chars.next().and_then(Direction::from_char_encoding), chars.next()) {
+                    (Some(direction), None) => Ok(EngineCommand::Move(ship_id, direction)),
+                    _ => Err(format!("unknown direction '{}'", direction)),
+                }
+            },
+            _ => Err(format!("malformed command '{}'", command.0)),
+        }
+    }
+
+    pub fn parse_all(commands: &[Command]) -> Result<Vec<EngineCommand>, String> {
+        commands.iter().map(EngineCommand::parse).collect()
+    }
+
+    // Splits a line of space separated commands, the way the server gets them
+    pub fn parse_line(line: &str) -> Result<Vec<EngineCommand>, String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let mut commands: Vec<EngineCommand> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let length = match tokens[i] {
+                "g" => 1,
+                "c" => 2,
+                "m" => 3,
+                token => return Err(format!("unknown command '{}'", token)),
+            };
+            if i + length > tokens.len() {
+                return Err(format!("truncated command '{}'", tokens[i..].join(" ")));
+            }
+            commands.push(EngineCommand::parse(&Command(tokens[i..i + length].join(" ")))?);
+            i += length;
+        }
+        Ok(commands)
+    }
+
+    fn parse_ship_id(token: &str) -> Result<ShipId, String> {
+        match token.parse() {
+            Ok(id) => Ok(ShipId(id)),
+            Err(_) => Err(format!("invalid ship id '{}'", token)),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use engine::commands::EngineCommand;
+use engine::simulator::Simulator;
+use hlt::command::Command;
+use hlt::game::Game;
+use hlt::PlayerId;
+use std::collections::HashMap;
+
+impl Game {
+    // The frame we expect to get next turn if our commands and the given enemy commands are
+    // carried out, with the engine's rounding for move costs, extraction and inspiration.
+    // Players without commands leave their ships where they are to mine and spawn nothing.
+    // Commands that don't parse, or that the server would eject the player for, are an error
+    // rather than something to guess around.
+    pub fn apply(&self, commands: &[Command], enemy_commands: &HashMap<PlayerId, Vec<Command>>) -> Result<Game, String> {
+        let mut all_commands: HashMap<PlayerId, Vec<EngineCommand>> = HashMap::new();
+        for player in &self.players {
+            let player_commands = if player.id == self.my_id {
+                commands
+            } else {
+                match enemy_commands.get(&player.id) {
+                    Some(player_commands) => player_commands,
+                    None => continue,
+                }
+            };
+            let parsed = match EngineCommand::parse_all(player_commands) {
+                Ok(parsed) => parsed,
+                Err(error) => return Err(format!("player {} sent {}", player.id.0, error)),
+            };
+            if let Err(error) = Simulator::check_commands(self, player.id, &parsed) {
+                return Err(format!("player {} {}", player.id.0, error));
+            }
+            all_commands.insert(player.id, parsed);
+        }
+
+        let mut simulator = Simulator::from_game(self);
+        simulator.process_turn(&all_commands);
+        Ok(simulator.game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::command::Command;
+    use hlt::direction::Direction;
+    use hlt::PlayerId;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use std::collections::HashMap;
+
+    #[test]
+    fn predicts_the_next_frame_with_the_engine_rounding() {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        testing::add_ship(&mut game, 0, 0, Position { x: 1, y: 1 }, 0);
+        testing::add_ship(&mut game, 0, 1, Position { x: 8, y: 1 }, 100);
+        testing::add_ship(&mut game, 1, 2, Position { x: 8, y: 8 }, 50);
+        game.map.at_position_mut(&Position { x: 1, y: 1 }).halite = 10;
+        game.map.at_position_mut(&Position { x: 8, y: 1 }).halite = 19;
+        game.map.at_position_mut(&Position { x: 8, y: 8 }).halite = 7;
+
+        let commands = vec![Command::move_ship(ShipId(0), Direction::Still), Command::move_ship(ShipId(1), Direction::South)];
+        let mut enemy_commands: HashMap<PlayerId, Vec<Command>> = HashMap::new();
+        enemy_commands.insert(PlayerId(1), vec![Command::spawn_ship()]);
+        let next = game.apply(&commands, &enemy_commands).unwrap();
+
+        assert_eq!(next.turn_number, game.turn_number + 1);
+        // 10 / 4 rounds up to 3, 19 / 10 rounds down to 1, 7 / 4 rounds up to 2
+        assert_eq!(next.ships[&ShipId(0)].halite, 3);
+        assert_eq!(next.map.at_position(&Position { x: 1, y: 1 }).halite, 7);
+        assert_eq!(next.ships[&ShipId(1)].halite, 99);
+        assert!(next.ships[&ShipId(1)].position.equal(&Position { x: 8, y: 2 }));
+        assert_eq!(next.ships[&ShipId(2)].halite, 52);
+        assert_eq!(next.map.at_position(&Position { x: 8, y: 8 }).halite, 5);
+        assert_eq!(next.players[1].halite, 4000);
+        assert!(next.ships.values().any(|ship| ship.id.0 == 3 && ship.position.equal(&Position { x: 12, y: 12 })));
+        assert_eq!(next.players[0].halite, 5000);
+    }
+
+    #[test]
+    fn rejects_commands_that_do_not_parse() {
+        let game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        let result = game.apply(&[Command("x 0".to_string())], &HashMap::new());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_commands_for_ships_the_player_does_not_own() {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        testing::add_ship(&mut game, 0, 0, Position { x: 1, y: 1 }, 0);
+        testing::add_ship(&mut game, 1, 1, Position { x: 8, y: 8 }, 0);
+
+        let result = game.apply(&[Command::move_ship(ShipId(1), Direction::North)], &HashMap::new());
+        assert_eq!(result.err().unwrap(), "player 0 gave a command to ship 1 which it doesn't own");
+        let result = game.apply(&[Command::move_ship(ShipId(7), Direction::North)], &HashMap::new());
+        assert!(result.is_err());
+
+        let mut enemy_commands: HashMap<PlayerId, Vec<Command>> = HashMap::new();
+        enemy_commands.insert(PlayerId(1), vec![Command::transform_ship_into_dropoff_site(ShipId(0))]);
+        let result = game.apply(&[], &enemy_commands);
+        assert_eq!(result.err().unwrap(), "player 1 gave a command to ship 0 which it doesn't own");
+    }
+
+    #[test]
+    fn rejects_several_commands_for_one_ship() {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        testing::add_ship(&mut game, 0, 0, Position { x: 1, y: 1 }, 0);
+
+        let commands = vec![Command::move_ship(ShipId(0), Direction::North), Command::move_ship(ShipId(0), Direction::Still)];
+        let result = game.apply(&commands, &HashMap::new());
+        assert_eq!(result.err().unwrap(), "player 0 gave ship 0 more than one command");
+
+        let commands = vec![Command::move_ship(ShipId(0), Direction::North), Command::transform_ship_into_dropoff_site(ShipId(0))];
+        assert!(game.apply(&commands, &HashMap::new()).is_err());
+    }
+}
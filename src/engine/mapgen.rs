@@ -0,0 +1,200 @@
+use hlt::game_map::GameMap;
+use hlt::map_cell::MapCell;
+use hlt::map_cell::Structure;
+use hlt::position::Position;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+pub const MIN_MAP_SIZE: usize = 32;
+pub const MAX_MAP_SIZE: usize = 64;
+
+// The knobs of the official generator, with the values the server sends as constants
+pub struct MapSettings {
+    pub persistence: f64,
+    pub factor_exp_1: f64,
+    pub factor_exp_2: f64,
+    pub min_cell_production: usize,
+    pub max_cell_production: usize,
+}
+
+impl MapSettings {
+    pub fn new() -> MapSettings {
+        MapSettings {
+            persistence: 0.7,
+            factor_exp_1: 2.0,
+            factor_exp_2: 2.0,
+            min_cell_production: 900,
+            max_cell_production: 1000,
+        }
+    }
+}
+
+pub struct GeneratedMap {
+    pub map: GameMap,
+    pub shipyards: Vec<Position>,
+}
+
+// Value noise over a width by height tile: random values on a lattice that gets twice as fine
+// every octave, smoothly interpolated, each octave weighing persistence times the one before.
+// The result is scaled to [0, 1].
+fn generate_noise(rng: &mut StdRng, width: usize, height: usize, persistence: f64) -> Vec<Vec<f64>> {
+    let mut noise = vec![vec![0.0; width]; height];
+    let octaves = (width.min(height) as f64).log2().floor() as u32 + 1;
+    let mut amplitude = 1.0;
+
+    for octave in 0..octaves {
+        let spacing = (width.max(height) as f64 / 2f64.powi(octave as i32)).max(1.0);
+        let lattice_width = (width as f64 / spacing).ceil() as usize + 2;
+        let lattice_height = (height as f64 / spacing).ceil() as usize + 2;
+        let lattice: Vec<Vec<f64>> = (0..lattice_height)
+            .map(|_| (0..lattice_width).map(|_| rng.gen::<f64>()).collect())
+            .collect();
+
+        for (y, row) in noise.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                let lattice_x = x as f64 / spacing;
+                let lattice_y = y as f64 / spacing;
+                let (x0, y0) = (lattice_x.floor() as usize, lattice_y.floor() as usize);
+                let tx = smoothstep(lattice_x - x0 as f64);
+                let ty = smoothstep(lattice_y - y0 as f64);
+                let top = lerp(lattice[y0][x0], lattice[y0][x0 + 1], tx);
+                let bottom = lerp(lattice[y0 + 1][x0], lattice[y0 + 1][x0 + 1], tx);
+                *value += amplitude * lerp(top, bottom, ty);
+            }
+        }
+        amplitude *= persistence;
+    }
+
+    let min = noise.iter().flatten().cloned().fold(f64::MAX, f64::min);
+    let max = noise.iter().flatten().cloned().fold(f64::MIN, f64::max);
+    let range = (max - min).max(f64::EPSILON);
+    for value in noise.iter_mut().flatten() {
+        *value = (*value - min) / range;
+    }
+    noise
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Builds a square map the way the official generator does: one tile of noise per player,
+// mirrored left to right for 2 players and into all four quadrants for 4 players, with each
+// shipyard in the middle of its tile. The same seed always gives the same map.
+pub fn generate(seed: u64, size: usize, num_players: usize, settings: &MapSettings) -> Result<GeneratedMap, String> {
+    if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&size) || !size.is_multiple_of(2) {
+        return Err(format!("map size must be even and between {} and {}, got {}", MIN_MAP_SIZE, MAX_MAP_SIZE, size));
+    }
+    let (tile_width, tile_height) = match num_players {
+        2 => (size / 2, size),
+        4 => (size / 2, size / 2),
+        _ => return Err(format!("only 2 and 4 player maps are supported, got {} players", num_players)),
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let noise = generate_noise(&mut rng, tile_width, tile_height, settings.persistence);
+
+    // Sharpen the noise so most of the halite sits in a few rich patches, then scale to this map's peak
+    let peak = rng.gen_range(settings.min_cell_production, settings.max_cell_production + 1) as f64;
+    let tile: Vec<Vec<usize>> = noise.iter()
+        .map(|row| row.iter().map(|value| (peak * value.powf(settings.factor_exp_1).powf(settings.factor_exp_2)).round() as usize).collect())
+        .collect();
+
+    let mut cells: Vec<Vec<MapCell>> = Vec::with_capacity(size);
+    for y in 0..size {
+        let mut row: Vec<MapCell> = Vec::with_capacity(size);
+        for x in 0..size {
+            let tile_x = if x < tile_width { x } else { size - 1 - x };
+            let tile_y = if y < tile_height { y } else { size - 1 - y };
+            let position = Position { x: x as i32, y: y as i32 };
+            row.push(MapCell { position, halite: tile[tile_y][tile_x], structure: Structure::None });
+        }
+        cells.push(row);
+    }
+
+    let (yard_x, yard_y) = ((tile_width / 2) as i32, (tile_height / 2) as i32);
+    let last = size as i32 - 1;
+    let shipyards = if num_players == 2 {
+        vec![Position { x: yard_x, y: yard_y }, Position { x: last - yard_x, y: yard_y }]
+    } else {
+        vec![
+            Position { x: yard_x, y: yard_y },
+            Position { x: last - yard_x, y: yard_y },
+            Position { x: yard_x, y: last - yard_y },
+            Position { x: last - yard_x, y: last - yard_y },
+        ]
+    };
+    // Shipyards start out empty
+    for shipyard in &shipyards {
+        cells[shipyard.y as usize][shipyard.x as usize].halite = 0;
+    }
+
+    Ok(GeneratedMap { map: GameMap { width: size, height: size, cells }, shipyards })
+}
+
+#[cfg(test)]
+mod tests {
+    use hlt::position::Position;
+    use super::generate;
+    use super::GeneratedMap;
+    use super::MapSettings;
+
+    fn get_halite(generated: &GeneratedMap) -> Vec<Vec<usize>> {
+        generated.map.cells.iter().map(|row| row.iter().map(|cell| cell.halite).collect()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_map() {
+        let settings = MapSettings::new();
+        let first = generate(42, 40, 2, &settings).unwrap();
+        let second = generate(42, 40, 2, &settings).unwrap();
+        let other = generate(43, 40, 2, &settings).unwrap();
+        assert!(get_halite(&first) == get_halite(&second));
+        assert!(get_halite(&first) != get_halite(&other));
+    }
+
+    #[test]
+    fn two_player_maps_mirror_left_to_right() {
+        let size = 32;
+        let generated = generate(7, size, 2, &MapSettings::new()).unwrap();
+        let halite = get_halite(&generated);
+        for row in &halite {
+            for x in 0..size {
+                assert_eq!(row[x], row[size - 1 - x]);
+            }
+        }
+        assert!(generated.shipyards[0].equal(&Position { x: 8, y: 16 }));
+        assert!(generated.shipyards[1].equal(&Position { x: 23, y: 16 }));
+    }
+
+    #[test]
+    fn four_player_maps_mirror_into_every_quadrant() {
+        let size = 48;
+        let generated = generate(7, size, 4, &MapSettings::new()).unwrap();
+        let halite = get_halite(&generated);
+        for y in 0..size {
+            for x in 0..size {
+                assert_eq!(halite[y][x], halite[y][size - 1 - x]);
+                assert_eq!(halite[y][x], halite[size - 1 - y][x]);
+            }
+        }
+        let expected = [(12, 12), (35, 12), (12, 35), (35, 35)];
+        assert_eq!(generated.shipyards.len(), expected.len());
+        for (shipyard, &(x, y)) in generated.shipyards.iter().zip(expected.iter()) {
+            assert!(shipyard.equal(&Position { x, y }));
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_sizes_and_player_counts() {
+        let settings = MapSettings::new();
+        assert!(generate(1, 33, 2, &settings).is_err());
+        assert!(generate(1, 80, 2, &settings).is_err());
+        assert!(generate(1, 32, 3, &settings).is_err());
+    }
+}
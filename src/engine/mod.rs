@@ -0,0 +1,10 @@
+pub mod ab_test;
+pub mod cli;
+pub mod commands;
+pub mod forward;
+pub mod mapgen;
+pub mod runner;
+pub mod simulator;
+#[cfg(test)]
+pub mod testing;
+pub mod tournament;
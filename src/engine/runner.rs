@@ -0,0 +1,460 @@
+use engine::commands::EngineCommand;
+use engine::mapgen;
+use engine::mapgen::MapSettings;
+use engine::simulator::Simulator;
+use engine::simulator::TurnEvents;
+use hlt::command::Command as BotCommand;
+use hlt::constants::Constants;
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::log::Mute;
+use hlt::PlayerId;
+use replay::writer::ReplayWriter;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::process::Child;
+use std::process::ChildStdin;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use strategy;
+use strategy::config::Config;
+use strategy::Services;
+use strategy::Strategy;
+
+pub struct MatchSettings {
+    pub seed: u64,
+    pub size: usize,
+    // Defaults to the number of turns the server plays on this map size
+    pub max_turns: Option<usize>,
+    pub init_timeout: Duration,
+    pub turn_timeout: Duration,
+    pub record_replay: bool,
+}
+
+impl MatchSettings {
+    pub fn new(seed: u64, size: usize) -> MatchSettings {
+        MatchSettings {
+            seed,
+            size,
+            max_turns: None,
+            init_timeout: Duration::from_secs(30),
+            turn_timeout: Duration::from_secs(2),
+            record_replay: false,
+        }
+    }
+}
+
+// Games last from 400 turns on the smallest maps to 500 on the largest
+pub fn get_max_turns(size: usize) -> usize {
+    400 + 100 * (size.max(mapgen::MIN_MAP_SIZE) - mapgen::MIN_MAP_SIZE) / (mapgen::MAX_MAP_SIZE - mapgen::MIN_MAP_SIZE)
+}
+
+// The constants line the server opens with, carrying every key the starter kits look for
+pub fn get_constants_line(settings: &MatchSettings, map_settings: &MapSettings) -> String {
+    let max_turns = settings.max_turns.unwrap_or_else(|| get_max_turns(settings.size));
+    format!(
+        "{{\"CAPTURE_ENABLED\": false, \"CAPTURE_RADIUS\": 3, \"DEFAULT_MAP_HEIGHT\": 48, \"DEFAULT_MAP_WIDTH\": 48, \
+        \"DROPOFF_COST\": 4000, \"DROPOFF_PENALTY_RATIO\": 4, \"EXTRACT_RATIO\": 4, \
+        \"FACTOR_EXP_1\": {:.1}, \"FACTOR_EXP_2\": {:.1}, \"INITIAL_ENERGY\": 5000, \"INSPIRATION_ENABLED\": true, \
+        \"INSPIRATION_RADIUS\": 4, \"INSPIRATION_SHIP_COUNT\": 2, \"INSPIRED_BONUS_MULTIPLIER\": 2.0, \
+        \"INSPIRED_EXTRACT_RATIO\": 4, \"INSPIRED_MOVE_COST_RATIO\": 10, \"MAX_CELL_PRODUCTION\": {}, \"MAX_ENERGY\": 1000, \
+        \"MAX_PLAYERS\": 16, \"MAX_TURNS\": {}, \"MAX_TURN_THRESHOLD\": {}, \"MIN_CELL_PRODUCTION\": {}, \"MIN_TURNS\": 400, \
+        \"MIN_TURN_THRESHOLD\": {}, \"MOVE_COST_RATIO\": 10, \"NEW_ENTITY_ENERGY_COST\": 1000, \"PERSISTENCE\": {:.1}, \
+        \"SHIPS_ABOVE_FOR_CAPTURE\": 3, \"STRICT_ERRORS\": false, \"game_seed\": {}}}",
+        map_settings.factor_exp_1, map_settings.factor_exp_2, map_settings.max_cell_production, max_turns,
+        mapgen::MAX_MAP_SIZE, map_settings.min_cell_production, mapgen::MIN_MAP_SIZE, map_settings.persistence, settings.seed)
+}
+
+// Everything Game::new reads after the constants: player count and id, shipyards, and the full map
+fn get_init_lines(game: &Game, player_id: PlayerId) -> Vec<String> {
+    let mut lines = vec![format!("{} {}", game.players.len(), player_id.0)];
+    for player in &game.players {
+        lines.push(format!("{} {} {}", player.id.0, player.shipyard.position.x, player.shipyard.position.y));
+    }
+    lines.push(format!("{} {}", game.map.width, game.map.height));
+    for row in &game.map.cells {
+        let halite: Vec<String> = row.iter().map(|cell| cell.halite.to_string()).collect();
+        lines.push(halite.join(" "));
+    }
+    lines
+}
+
+// Everything Game::update_frame reads: the turn, every player's ships and dropoffs, and the cells
+// whose halite changed since the previous frame
+fn get_frame_lines(game: &Game, previous_halite: &[Vec<usize>]) -> Vec<String> {
+    let mut lines = vec![game.turn_number.to_string()];
+    for player in &game.players {
+        lines.push(format!("{} {} {} {}", player.id.0, player.ship_ids.len(), player.dropoff_ids.len(), player.halite));
+        for ship_id in &player.ship_ids {
+            let ship = &game.ships[ship_id];
+            lines.push(format!("{} {} {} {}", ship.id.0, ship.position.x, ship.position.y, ship.halite));
+        }
+        for dropoff_id in &player.dropoff_ids {
+            let dropoff = &game.dropoffs[dropoff_id];
+            lines.push(format!("{} {} {}", dropoff.id.0, dropoff.position.x, dropoff.position.y));
+        }
+    }
+
+    let mut updates: Vec<String> = Vec::new();
+    for row in &game.map.cells {
+        for cell in row {
+            if cell.halite != previous_halite[cell.position.y as usize][cell.position.x as usize] {
+                updates.push(format!("{} {} {}", cell.position.x, cell.position.y, cell.halite));
+            }
+        }
+    }
+    lines.push(updates.len().to_string());
+    lines.extend(updates);
+    lines
+}
+
+// A bot running as a child process. Its output is read on a separate thread so that we can
+// stop waiting for it when it runs out of time. Each line is timed from when the bot was last
+// sent something to when the line came in, however long we take to get around to reading it.
+pub struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<(Instant, String)>,
+    sent_at: Instant,
+}
+
+impl BotProcess {
+    pub fn launch(command: &str) -> Result<BotProcess, String> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("couldn't start '{}': {}", command, error))?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => if sender.send((Instant::now(), line)).is_err() { break },
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Ok(BotProcess { child, stdin, lines, sent_at: Instant::now() })
+    }
+
+    pub fn send(&mut self, lines: &[String]) -> Result<(), String> {
+        let mut message = lines.join("\n");
+        message.push('\n');
+        self.sent_at = Instant::now();
+        self.stdin.write_all(message.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|_| "closed its input".to_string())
+    }
+
+    pub fn receive(&mut self, timeout: Duration) -> Result<String, String> {
+        let deadline = self.sent_at + timeout;
+        match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok((received_at, line)) if received_at <= deadline => Ok(line),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => Err(format!("took longer than {} ms", timeout.as_millis())),
+            Err(RecvTimeoutError::Disconnected) => Err("exited".to_string()),
+        }
+    }
+
+    pub fn kill(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+// Bots given as "builtin:<strategy> [key=value ...]" are one of our strategies playing inside the
+// runner, with the same arguments the bot executable takes after its seed
+pub const BUILTIN_PREFIX: &str = "builtin:";
+
+// A strategy playing inside the runner. It sees the game the way a bot process would rebuild it
+// from the frames, but isn't held to the time limits.
+pub struct BuiltinBot {
+    strategy: Box<dyn Strategy>,
+    services: Option<Services>,
+    rng_seed: u64,
+    // Nobody reads the runner's log, and with every builtin bot writing to it it only grows
+    _mute: Mute,
+}
+
+impl BuiltinBot {
+    pub fn new(spec: &str, num_players: usize, rng_seed: u64) -> Result<BuiltinBot, String> {
+        let args: Vec<String> = spec.split_whitespace().map(|arg| arg.to_string()).collect();
+        let name = match args.iter().find(|arg| Config::split_setting(arg).is_none()) {
+            Some(name) => name.clone(),
+            None => strategy::DEFAULT_STRATEGY.to_string(),
+        };
+        if !strategy::get_strategy_names().contains(&name.as_str()) {
+            return Err(format!("unknown strategy '{}', available strategies: {}", name, strategy::get_strategy_names().join(", ")));
+        }
+        let config = Config::try_from_args(&args, num_players)?;
+        let strategy = strategy::from_name(&name, config);
+        Ok(BuiltinBot { strategy, services: None, rng_seed, _mute: Log::mute() })
+    }
+
+    pub fn init(&mut self, game: &Game) -> String {
+        self.services = Some(Services::new(game, self.rng_seed));
+        self.strategy.name().to_string()
+    }
+
+    pub fn play_turn(&mut self, game: &Game) -> Vec<BotCommand> {
+        let services = self.services.as_mut().unwrap();
+        services.update_frame(game);
+        self.strategy.play_turn(game, services)
+    }
+}
+
+pub enum Bot {
+    Process(BotProcess),
+    Builtin(Box<BuiltinBot>),
+}
+
+impl Bot {
+    pub fn launch(command: &str, num_players: usize, rng_seed: u64) -> Result<Bot, String> {
+        match command.strip_prefix(BUILTIN_PREFIX) {
+            Some(spec) => BuiltinBot::new(spec, num_players, rng_seed).map(|bot| Bot::Builtin(Box::new(bot))),
+            None => BotProcess::launch(command).map(Bot::Process),
+        }
+    }
+
+    pub fn kill(&mut self) {
+        if let Bot::Process(bot) = self {
+            bot.kill();
+        }
+    }
+}
+
+pub struct PlayerResult {
+    pub player_id: PlayerId,
+    pub name: String,
+    pub command: String,
+    pub rank: usize,
+    pub halite: usize,
+    // Turn and reason when the player was ejected or ran out of ships
+    pub elimination: Option<(usize, String)>,
+}
+
+pub struct MatchResult {
+    pub seed: u64,
+    pub size: usize,
+    pub turns: usize,
+    pub players: Vec<PlayerResult>,
+}
+
+// Plays the server's part for a whole game between bot executables, one per player
+pub struct Match {
+    pub simulator: Simulator,
+    pub bots: Vec<Option<Bot>>,
+    pub commands: Vec<String>,
+    pub names: Vec<String>,
+    pub replay: Option<ReplayWriter>,
+    settings: MatchSettings,
+    constants_line: String,
+    previous_halite: Vec<Vec<usize>>,
+}
+
+impl Match {
+    pub fn new(settings: MatchSettings, commands: &[String]) -> Result<Match, String> {
+        if commands.len() != 2 && commands.len() != 4 {
+            return Err(format!("a game needs 2 or 4 bots, got {}", commands.len()));
+        }
+
+        let map_settings = MapSettings::new();
+        let constants_line = get_constants_line(&settings, &map_settings);
+        let generated = mapgen::generate(settings.seed, settings.size, commands.len(), &map_settings)?;
+        let simulator = Simulator::new(Constants::new(&constants_line), generated.map, &generated.shipyards);
+        let previous_halite = simulator.game.map.cells.iter().map(|row| row.iter().map(|cell| cell.halite).collect()).collect();
+        let replay = if settings.record_replay {
+            Some(ReplayWriter::new(&constants_line, settings.seed, &simulator.game))
+        } else {
+            None
+        };
+
+        let mut bots: Vec<Option<Bot>> = Vec::new();
+        for (id, command) in commands.iter().enumerate() {
+            bots.push(Some(Bot::launch(command, commands.len(), settings.seed + id as u64)?));
+        }
+        Ok(Match { simulator, bots, commands: commands.to_vec(), names: commands.to_vec(), replay, settings, constants_line, previous_halite })
+    }
+
+    fn eject(&mut self, player_id: PlayerId, reason: &str) {
+        self.simulator.eject(player_id, reason);
+        if let Some(bot) = &mut self.bots[player_id.0] {
+            bot.kill();
+        }
+        self.bots[player_id.0] = None;
+    }
+
+    // Sends every bot the initial state and waits for its name
+    pub fn init(&mut self) {
+        for id in 0..self.bots.len() {
+            let mut lines = vec![self.constants_line.clone()];
+            lines.extend(get_init_lines(&self.simulator.game, PlayerId(id)));
+            let init_timeout = self.settings.init_timeout;
+            let result = match &mut self.bots[id] {
+                Some(Bot::Process(bot)) => bot.send(&lines).and_then(|_| bot.receive(init_timeout)),
+                Some(Bot::Builtin(bot)) => Ok(bot.init(&self.simulator.get_view(PlayerId(id)))),
+                None => continue,
+            };
+            match result {
+                Ok(name) => self.names[id] = name.trim().to_string(),
+                Err(reason) => self.eject(PlayerId(id), &format!("failed to start: {}", reason)),
+            }
+        }
+    }
+
+    // Sends the current frame to every bot still in the game and plays out their commands. Bot
+    // processes think at the same time and each is held to the time limit on its own, builtin
+    // bots take their turns in between.
+    pub fn play_turn(&mut self) -> TurnEvents {
+        let frame = get_frame_lines(&self.simulator.game, &self.previous_halite);
+        let before = self.replay.as_ref().map(|_| self.simulator.game.clone());
+        self.previous_halite = self.simulator.game.map.cells.iter().map(|row| row.iter().map(|cell| cell.halite).collect()).collect();
+
+        // Every bot process gets the frame before we wait on anyone, so they all think at the same time
+        let mut failures: Vec<(PlayerId, String)> = Vec::new();
+        for player_id in self.simulator.get_alive_players() {
+            if let Some(Bot::Process(bot)) = &mut self.bots[player_id.0] {
+                if let Err(reason) = bot.send(&frame) {
+                    failures.push((player_id, reason));
+                }
+            }
+        }
+
+        let mut commands: HashMap<PlayerId, Vec<EngineCommand>> = HashMap::new();
+        for player_id in self.simulator.get_alive_players() {
+            if failures.iter().any(|(failed_id, _)| *failed_id == player_id) {
+                continue;
+            }
+            let turn_timeout = self.settings.turn_timeout;
+            let player_commands = match &mut self.bots[player_id.0] {
+                Some(Bot::Process(bot)) => bot.receive(turn_timeout).and_then(|line| EngineCommand::parse_line(&line)),
+                Some(Bot::Builtin(bot)) => EngineCommand::parse_all(&bot.play_turn(&self.simulator.get_view(player_id))),
+                None => continue,
+            };
+            match player_commands {
+                Ok(player_commands) => { commands.insert(player_id, player_commands); },
+                Err(reason) => failures.push((player_id, reason)),
+            }
+        }
+        for (player_id, reason) in &failures {
+            self.eject(*player_id, reason);
+        }
+
+        let mut events = self.simulator.process_turn(&commands);
+        if let (Some(replay), Some(before)) = (&mut self.replay, &before) {
+            replay.record_turn(before, &commands, &events, &self.simulator);
+        }
+        // The simulator ejects players for breaking the rules, their processes go too
+        for (player_id, _) in &events.eliminations {
+            if let Some(bot) = &mut self.bots[player_id.0] {
+                bot.kill();
+            }
+            self.bots[player_id.0] = None;
+        }
+        events.eliminations.extend(failures);
+        events
+    }
+
+    pub fn finish(&mut self) -> MatchResult {
+        for bot in self.bots.iter_mut().flatten() {
+            bot.kill();
+        }
+        let rankings = self.simulator.get_rankings();
+        let game = &self.simulator.game;
+        let players = game.players.iter()
+            .map(|player| PlayerResult {
+                player_id: player.id,
+                name: self.names[player.id.0].clone(),
+                command: self.commands[player.id.0].clone(),
+                rank: rankings.iter().position(|player_id| *player_id == player.id).unwrap() + 1,
+                halite: player.halite,
+                elimination: self.simulator.eliminations[player.id.0].as_ref().map(|elimination| (elimination.turn_number, elimination.reason.clone())),
+            })
+            .collect();
+        MatchResult { seed: self.settings.seed, size: self.settings.size, turns: game.turn_number - 1, players }
+    }
+
+    pub fn write_replay(&self, path: &str) -> Result<(), String> {
+        match &self.replay {
+            Some(replay) => replay.write(path, &self.simulator, &self.names),
+            None => Err("the match wasn't recorded".to_string()),
+        }
+    }
+
+    pub fn run(&mut self) -> MatchResult {
+        self.init();
+        while !self.simulator.is_over() {
+            self.play_turn();
+        }
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hlt::log::Log;
+    use std::thread;
+    use std::time::Duration;
+    use super::BotProcess;
+    use super::BuiltinBot;
+    use super::Match;
+    use super::MatchSettings;
+
+    #[test]
+    fn bad_builtin_settings_are_errors() {
+        assert!(BuiltinBot::new("overlord min_cel_halite=3", 2, 0).is_err());
+        assert!(BuiltinBot::new("overlord 4p.min_cell_halite=lots", 2, 0).is_err());
+        assert!(BuiltinBot::new("overlord config=does-not-exist.cfg", 2, 0).is_err());
+        assert!(BuiltinBot::new("nobody", 2, 0).is_err());
+    }
+
+    #[test]
+    fn bad_map_sizes_are_errors() {
+        let commands = vec!["builtin:starter".to_string(), "builtin:starter".to_string()];
+        assert!(Match::new(MatchSettings::new(1, 33), &commands).is_err());
+    }
+
+    #[test]
+    fn each_bot_process_is_timed_from_its_own_frame() {
+        let mut quick = BotProcess::launch("read line; echo quick").unwrap();
+        let mut slow = BotProcess::launch("read line; sleep 0.6; echo slow").unwrap();
+        quick.send(&["frame".to_string()]).unwrap();
+        slow.send(&["frame".to_string()]).unwrap();
+
+        // Answering in time counts even when we only get to the answer later
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(quick.receive(Duration::from_millis(200)).unwrap(), "quick");
+        // Time spent waiting on the other bot counts against this one
+        assert!(slow.receive(Duration::from_millis(400)).is_err());
+    }
+
+    #[test]
+    fn builtin_bots_mute_the_log_while_they_are_around() {
+        let first = BuiltinBot::new("starter", 2, 0).unwrap();
+        let second = BuiltinBot::new("starter", 2, 0).unwrap();
+        assert!(Log::is_muted());
+        drop(first);
+        assert!(Log::is_muted());
+        drop(second);
+    }
+}
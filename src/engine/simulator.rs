@@ -0,0 +1,558 @@
+use engine::commands::EngineCommand;
+use hlt::constants::Constants;
+use hlt::direction::Direction;
+use hlt::dropoff::Dropoff;
+use hlt::DropoffId;
+use hlt::game::Game;
+use hlt::game_map::GameMap;
+use hlt::map_cell::Structure;
+use hlt::player::Player;
+use hlt::PlayerId;
+use hlt::position::Position;
+use hlt::ship::Ship;
+use hlt::ShipId;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+pub struct Collision {
+    pub position: Position,
+    pub ship_ids: Vec<ShipId>,
+}
+
+// What happened during a turn besides ships moving around
+pub struct TurnEvents {
+    // The new ships as they came out of the shipyards
+    pub spawns: Vec<Ship>,
+    pub constructions: Vec<DropoffId>,
+    pub collisions: Vec<Collision>,
+    // Halite each player's ships brought home and dug up this turn
+    pub deposited: HashMap<PlayerId, usize>,
+    pub mined: HashMap<PlayerId, usize>,
+    // Where the deposits were made, and how much of what was dug up came from inspiration
+    pub deposits: Vec<(Position, usize)>,
+    pub bonus: HashMap<PlayerId, usize>,
+    pub failed_commands: Vec<(PlayerId, String)>,
+    pub eliminations: Vec<(PlayerId, String)>,
+}
+
+impl TurnEvents {
+    fn new() -> TurnEvents {
+        TurnEvents { spawns: Vec::new(), constructions: Vec::new(), collisions: Vec::new(), deposited: HashMap::new(), mined: HashMap::new(), deposits: Vec::new(), bonus: HashMap::new(), failed_commands: Vec::new(), eliminations: Vec::new() }
+    }
+}
+
+pub struct Elimination {
+    pub turn_number: usize,
+    pub reason: String,
+}
+
+// The Halite III rules, played out on the same Game the bots see. Turn numbers start at 1 like
+// the server's; after max_turns turns have been processed the game is over.
+//
+// Commands that break the protocol (unknown or foreign ships, several commands for one ship,
+// several spawns) get the player ejected like the server does. Commands that are valid but
+// can't be carried out, a move or spawn without the halite for it or a dropoff on top of
+// another structure, are skipped and the ship stays where it is.
+pub struct Simulator {
+    pub game: Game,
+    pub inspired: HashSet<ShipId>,
+    pub halite_history: Vec<Vec<usize>>,
+    pub eliminations: Vec<Option<Elimination>>,
+    next_ship_id: usize,
+    next_dropoff_id: usize,
+}
+
+impl Simulator {
+    pub fn new(constants: Constants, mut map: GameMap, shipyards: &[Position]) -> Simulator {
+        let initial_halite = constants.initial_halite;
+        let mut players: Vec<Player> = Vec::new();
+        for (id, position) in shipyards.iter().enumerate() {
+            let position = map.normalize(position);
+            map.at_position_mut(&position).structure = Structure::Shipyard(PlayerId(id));
+            players.push(Player::new(PlayerId(id), position, initial_halite));
+        }
+
+        let mut game = Game::from_state(constants, PlayerId(0), players, map);
+        game.turn_number = 1;
+        Simulator {
+            game,
+            inspired: HashSet::new(),
+            halite_history: vec![Vec::new(); shipyards.len()],
+            eliminations: shipyards.iter().map(|_| None).collect(),
+            next_ship_id: 0,
+            next_dropoff_id: 0,
+        }
+    }
+
+    // Picks up from a frame a bot got, with everybody still in the game. The server's next ids
+    // aren't part of the frame, so new ships and dropoffs get the ids following the highest seen.
+    pub fn from_game(game: &Game) -> Simulator {
+        let next_ship_id = game.ships.keys().map(|ship_id| ship_id.0 + 1).max().unwrap_or(0);
+        let next_dropoff_id = game.dropoffs.keys().map(|dropoff_id| dropoff_id.0 + 1).max().unwrap_or(0);
+        Simulator {
+            game: game.clone(),
+            inspired: HashSet::new(),
+            halite_history: vec![Vec::new(); game.players.len()],
+            eliminations: game.players.iter().map(|_| None).collect(),
+            next_ship_id,
+            next_dropoff_id,
+        }
+    }
+
+    // The game as the given player gets to see it this turn
+    pub fn get_view(&self, player_id: PlayerId) -> Game {
+        let mut view = self.game.clone();
+        view.my_id = player_id;
+        view
+    }
+
+    pub fn is_alive(&self, player_id: PlayerId) -> bool {
+        self.eliminations[player_id.0].is_none()
+    }
+
+    pub fn get_alive_players(&self) -> Vec<PlayerId> {
+        self.game.players.iter().map(|player| player.id).filter(|player_id| self.is_alive(*player_id)).collect()
+    }
+
+    pub fn is_over(&self) -> bool {
+        let alive = self.get_alive_players().len();
+        self.game.turn_number > self.game.constants.max_turns || alive == 0 || (self.game.players.len() > 1 && alive == 1)
+    }
+
+    // Kicks a player out of the game, their ships sink with their cargo but their structures stay
+    pub fn eject(&mut self, player_id: PlayerId, reason: &str) {
+        if !self.is_alive(player_id) {
+            return;
+        }
+        self.eliminations[player_id.0] = Some(Elimination { turn_number: self.game.turn_number, reason: reason.to_string() });
+        let player = &mut self.game.players[player_id.0];
+        for ship_id in &player.ship_ids {
+            self.game.ships.remove(ship_id);
+        }
+        player.ship_ids.clear();
+    }
+
+    // Players still in the game come first, by halite and then by their halite on earlier turns,
+    // the others after them, the ones that lasted longest first
+    pub fn get_rankings(&self) -> Vec<PlayerId> {
+        let mut player_ids: Vec<PlayerId> = self.game.players.iter().map(|player| player.id).collect();
+        player_ids.sort_by(|a, b| {
+            let elimination_a = self.eliminations[a.0].as_ref().map(|elimination| elimination.turn_number);
+            let elimination_b = self.eliminations[b.0].as_ref().map(|elimination| elimination.turn_number);
+            let lasted = match (elimination_a, elimination_b) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Less,
+                (Some(_), None) => Ordering::Greater,
+                (Some(turn_a), Some(turn_b)) => turn_b.cmp(&turn_a),
+            };
+            let history_a = self.halite_history[a.0].iter().rev();
+            let history_b = self.halite_history[b.0].iter().rev();
+            lasted
+                .then(self.game.players[b.0].halite.cmp(&self.game.players[a.0].halite))
+                .then(history_b.cmp(history_a))
+                .then(a.0.cmp(&b.0))
+        });
+        player_ids
+    }
+
+    // Ships with enough enemy ships around them collect more and pay less to move
+    fn update_inspiration(&mut self) {
+        let game = &self.game;
+        let constants = &game.constants;
+        self.inspired.clear();
+        if !constants.inspiration_enabled {
+            return;
+        }
+        for ship in game.ships.values() {
+            let enemies = game.ships.values()
+                .filter(|other| other.owner != ship.owner)
+                .filter(|other| game.map.calculate_distance(&other.position, &ship.position) <= constants.inspiration_radius)
+                .count();
+            if enemies >= constants.inspiration_ship_count {
+                self.inspired.insert(ship.id);
+            }
+        }
+    }
+
+    // Checks that every command is one the server would accept, ejecting players that send anything else
+    fn validate<'a>(&mut self, commands: &'a HashMap<PlayerId, Vec<EngineCommand>>, events: &mut TurnEvents) -> Vec<(PlayerId, Vec<&'a EngineCommand>)> {
+        let mut valid: Vec<(PlayerId, Vec<&EngineCommand>)> = Vec::new();
+        for player_id in self.get_alive_players() {
+            let player_commands = match commands.get(&player_id) {
+                Some(player_commands) => player_commands,
+                None => continue,
+            };
+
+            match Simulator::check_commands(&self.game, player_id, player_commands) {
+                Err(reason) => {
+                    self.eject(player_id, &reason);
+                    events.eliminations.push((player_id, reason));
+                },
+                Ok(()) => valid.push((player_id, player_commands.iter().collect())),
+            }
+        }
+        valid
+    }
+
+    // The first reason the server would have to eject the player for these commands, if any
+    pub fn check_commands(game: &Game, player_id: PlayerId, commands: &[EngineCommand]) -> Result<(), String> {
+        let mut spawns = 0;
+        let mut commanded: HashSet<ShipId> = HashSet::new();
+        for command in commands {
+            match command.get_ship_id() {
+                None => {
+                    spawns += 1;
+                    if spawns > 1 {
+                        return Err("spawned more than once in a turn".to_string());
+                    }
+                },
+                Some(ship_id) => {
+                    if game.ships.get(&ship_id).is_none_or(|ship| ship.owner != player_id) {
+                        return Err(format!("gave a command to ship {} which it doesn't own", ship_id.0));
+                    } else if !commanded.insert(ship_id) {
+                        return Err(format!("gave ship {} more than one command", ship_id.0));
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn construct(&mut self, commands: &[(PlayerId, Vec<&EngineCommand>)], events: &mut TurnEvents) {
+        let mut sites: Vec<(PlayerId, ShipId)> = Vec::new();
+        for (player_id, player_commands) in commands {
+            for command in player_commands {
+                if let EngineCommand::Construct(ship_id) = command {
+                    sites.push((*player_id, *ship_id));
+                }
+            }
+        }
+
+        let positions: Vec<Position> = sites.iter().map(|(_, ship_id)| self.game.ships[ship_id].position).collect();
+        for (i, (player_id, ship_id)) in sites.iter().enumerate() {
+            let position = positions[i];
+            // Two dropoffs on the same cell both fail
+            let is_contested = positions.iter().enumerate().any(|(j, other)| j != i && other.equal(&position));
+            let cell_halite = self.game.map.at_position(&position).halite;
+            let available = self.game.players[player_id.0].halite + self.game.ships[ship_id].halite + cell_halite;
+            if is_contested || self.game.map.at_position(&position).structure.is_some() {
+                events.failed_commands.push((*player_id, format!("ship {} can't build a dropoff at {}, {}", ship_id.0, position.x, position.y)));
+                continue;
+            }
+            if available < self.game.constants.dropoff_cost {
+                events.failed_commands.push((*player_id, format!("ship {} can't afford a dropoff", ship_id.0)));
+                continue;
+            }
+
+            let dropoff_id = DropoffId(self.next_dropoff_id);
+            self.next_dropoff_id += 1;
+            self.game.players[player_id.0].halite = available - self.game.constants.dropoff_cost;
+            let cell = self.game.map.at_position_mut(&position);
+            cell.halite = 0;
+            cell.structure = Structure::Dropoff(dropoff_id);
+            self.game.dropoffs.insert(dropoff_id, Dropoff { owner: *player_id, id: dropoff_id, position });
+            self.game.players[player_id.0].dropoff_ids.push(dropoff_id);
+            self.game.ships.remove(ship_id);
+            events.constructions.push(dropoff_id);
+        }
+    }
+
+    // Returns the ships that moved off their cell
+    fn move_ships(&mut self, commands: &[(PlayerId, Vec<&EngineCommand>)], events: &mut TurnEvents) -> HashSet<ShipId> {
+        let mut moved: HashSet<ShipId> = HashSet::new();
+        for (player_id, player_commands) in commands {
+            for command in player_commands {
+                let (ship_id, direction) = match command {
+                    EngineCommand::Move(ship_id, direction) if *direction != Direction::Still => (ship_id, *direction),
+                    _ => continue,
+                };
+                // The ship may have turned into a dropoff
+                let ship = match self.game.ships.get_mut(ship_id) {
+                    Some(ship) => ship,
+                    None => continue,
+                };
+                let move_cost_ratio = if self.inspired.contains(ship_id) {
+                    self.game.constants.inspired_move_cost_ratio
+                } else {
+                    self.game.constants.move_cost_ratio
+                };
+                let move_cost = self.game.map.at_position(&ship.position).halite / move_cost_ratio;
+                if ship.halite < move_cost {
+                    events.failed_commands.push((*player_id, format!("ship {} can't afford to move", ship_id.0)));
+                    continue;
+                }
+                ship.halite -= move_cost;
+                ship.position = self.game.map.normalize(&ship.position.directional_offset(direction));
+                moved.insert(*ship_id);
+            }
+        }
+        moved
+    }
+
+    fn spawn(&mut self, commands: &[(PlayerId, Vec<&EngineCommand>)], events: &mut TurnEvents) {
+        for (player_id, player_commands) in commands {
+            if !player_commands.iter().any(|command| matches!(command, EngineCommand::Spawn)) {
+                continue;
+            }
+            let ship_cost = self.game.constants.ship_cost;
+            let player = &mut self.game.players[player_id.0];
+            if player.halite < ship_cost {
+                events.failed_commands.push((*player_id, "can't afford a ship".to_string()));
+                continue;
+            }
+            player.halite -= ship_cost;
+            let ship_id = ShipId(self.next_ship_id);
+            self.next_ship_id += 1;
+            let ship = Ship::new(*player_id, ship_id, player.shipyard.position, 0, self.game.constants.max_halite);
+            self.game.ships.insert(ship_id, ship.clone());
+            events.spawns.push(ship);
+        }
+    }
+
+    // Ships sharing a cell all sink. Their cargo goes into the sea, or to the owner of the structure they sank on.
+    fn resolve_collisions(&mut self, events: &mut TurnEvents) {
+        let mut ships_by_cell: HashMap<(i32, i32), Vec<ShipId>> = HashMap::new();
+        for ship in self.game.ships.values() {
+            ships_by_cell.entry((ship.position.x, ship.position.y)).or_default().push(ship.id);
+        }
+        let mut cells: Vec<(i32, i32)> = ships_by_cell.iter()
+            .filter(|(_, ship_ids)| ship_ids.len() > 1)
+            .map(|(cell, _)| *cell)
+            .collect();
+        cells.sort_by_key(|(x, y)| (*y, *x));
+
+        for (x, y) in cells {
+            let position = Position { x, y };
+            let mut ship_ids = ships_by_cell.remove(&(x, y)).unwrap();
+            ship_ids.sort_by_key(|ship_id| ship_id.0);
+            let cargo: usize = ship_ids.iter().map(|ship_id| self.game.ships[ship_id].halite).sum();
+            let owner = match self.game.map.at_position(&position).structure {
+                Structure::Shipyard(player_id) => Some(player_id),
+                Structure::Dropoff(dropoff_id) => Some(self.game.dropoffs[&dropoff_id].owner),
+                Structure::None => None,
+            };
+            match owner {
+                Some(player_id) => self.game.players[player_id.0].halite += cargo,
+                None => self.game.map.at_position_mut(&position).halite += cargo,
+            }
+            for ship_id in &ship_ids {
+                self.game.ships.remove(ship_id);
+            }
+            events.collisions.push(Collision { position, ship_ids });
+        }
+    }
+
+    fn deposit(&mut self, events: &mut TurnEvents) {
+        let game = &mut self.game;
+        for ship in game.ships.values_mut() {
+            let is_own_structure = match game.map.at_position(&ship.position).structure {
+                Structure::Shipyard(player_id) => player_id == ship.owner,
+                Structure::Dropoff(dropoff_id) => game.dropoffs[&dropoff_id].owner == ship.owner,
+                Structure::None => false,
+            };
+            if is_own_structure {
+                game.players[ship.owner.0].halite += ship.halite;
+                *events.deposited.entry(ship.owner).or_insert(0) += ship.halite;
+                if ship.halite > 0 {
+                    events.deposits.push((ship.position, ship.halite));
+                }
+                ship.halite = 0;
+            }
+        }
+    }
+
+    // Ships that stayed on their cell extract a quarter of it, rounded up, and inspired ships get a bonus on top
+    fn mine(&mut self, moved: &HashSet<ShipId>, events: &mut TurnEvents) {
+        let game = &mut self.game;
+        let constants = &game.constants;
+        for ship in game.ships.values_mut() {
+            if moved.contains(&ship.id) || events.spawns.iter().any(|spawned| spawned.id == ship.id) {
+                continue;
+            }
+            let is_inspired = self.inspired.contains(&ship.id);
+            let extract_ratio = if is_inspired { constants.inspired_extract_ratio } else { constants.extract_ratio };
+            let cell = game.map.at_position_mut(&ship.position);
+            let space = constants.max_halite.saturating_sub(ship.halite);
+            let extracted = cell.halite.div_ceil(extract_ratio).min(space);
+            let mut gained = extracted;
+            if is_inspired {
+                gained += (extracted as f64 * constants.inspired_bonus_multiplier) as usize;
+            }
+            cell.halite -= extracted;
+            ship.halite += gained.min(space);
+            *events.mined.entry(ship.owner).or_insert(0) += gained.min(space);
+            if gained.min(space) > extracted {
+                *events.bonus.entry(ship.owner).or_insert(0) += gained.min(space) - extracted;
+            }
+        }
+    }
+
+    // Players with no ships left and not enough halite to build one are out
+    fn eliminate_stranded(&mut self, events: &mut TurnEvents) {
+        for player_id in self.get_alive_players() {
+            let player = &self.game.players[player_id.0];
+            if player.ship_ids.is_empty() && player.halite < self.game.constants.ship_cost {
+                let reason = "has no ships and can't afford a new one".to_string();
+                self.eliminations[player_id.0] = Some(Elimination { turn_number: self.game.turn_number, reason: reason.clone() });
+                events.eliminations.push((player_id, reason));
+            }
+        }
+    }
+
+    fn refresh_players(&mut self) {
+        let game = &mut self.game;
+        for player in &mut game.players {
+            player.ship_ids = game.ships.values().filter(|ship| ship.owner == player.id).map(|ship| ship.id).collect();
+            player.ship_ids.sort_by_key(|ship_id| ship_id.0);
+            player.dropoff_ids.sort_by_key(|dropoff_id| dropoff_id.0);
+        }
+    }
+
+    // Plays out one turn from every player's commands, in the order the server does: dropoffs get
+    // built, ships get spawned and moved, ships sharing a cell sink, ships on a base deposit and
+    // ships that stayed put mine.
+    pub fn process_turn(&mut self, commands: &HashMap<PlayerId, Vec<EngineCommand>>) -> TurnEvents {
+        let mut events = TurnEvents::new();
+        self.update_inspiration();
+
+        let valid = self.validate(commands, &mut events);
+        self.construct(&valid, &mut events);
+        let moved = self.move_ships(&valid, &mut events);
+        self.spawn(&valid, &mut events);
+        self.resolve_collisions(&mut events);
+        self.deposit(&mut events);
+        self.mine(&moved, &mut events);
+        self.refresh_players();
+        self.eliminate_stranded(&mut events);
+
+        for player in &self.game.players {
+            self.halite_history[player.id.0].push(player.halite);
+        }
+        self.game.turn_number += 1;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::commands::EngineCommand;
+    use engine::testing;
+    use hlt::direction::Direction;
+    use hlt::game::Game;
+    use hlt::map_cell::Structure;
+    use hlt::PlayerId;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use std::collections::HashMap;
+    use super::Simulator;
+    use super::TurnEvents;
+
+    fn get_game() -> Game {
+        testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }])
+    }
+
+    fn play(game: &Game, commands: Vec<(usize, EngineCommand)>) -> (Simulator, TurnEvents) {
+        let mut all_commands: HashMap<PlayerId, Vec<EngineCommand>> = HashMap::new();
+        for (player_id, command) in commands {
+            all_commands.entry(PlayerId(player_id)).or_default().push(command);
+        }
+        let mut simulator = Simulator::from_game(game);
+        let events = simulator.process_turn(&all_commands);
+        (simulator, events)
+    }
+
+    #[test]
+    fn extraction_rounds_up_and_move_cost_rounds_down() {
+        let mut game = get_game();
+        testing::add_ship(&mut game, 0, 0, Position { x: 1, y: 1 }, 0);
+        testing::add_ship(&mut game, 0, 1, Position { x: 8, y: 1 }, 100);
+        game.map.at_position_mut(&Position { x: 1, y: 1 }).halite = 10;
+        game.map.at_position_mut(&Position { x: 8, y: 1 }).halite = 19;
+
+        let (simulator, _) = play(&game, vec![(0, EngineCommand::Move(ShipId(1), Direction::East))]);
+        assert_eq!(simulator.game.ships[&ShipId(0)].halite, 3);
+        assert_eq!(simulator.game.map.at_position(&Position { x: 1, y: 1 }).halite, 7);
+        assert_eq!(simulator.game.ships[&ShipId(1)].halite, 99);
+        assert!(simulator.game.ships[&ShipId(1)].position.equal(&Position { x: 9, y: 1 }));
+    }
+
+    #[test]
+    fn inspired_ships_get_the_bonus() {
+        let mut game = get_game();
+        testing::add_ship(&mut game, 0, 0, Position { x: 1, y: 8 }, 0);
+        testing::add_ship(&mut game, 1, 1, Position { x: 3, y: 8 }, 0);
+        testing::add_ship(&mut game, 1, 2, Position { x: 1, y: 10 }, 0);
+        game.map.at_position_mut(&Position { x: 1, y: 8 }).halite = 100;
+
+        let (simulator, _) = play(&game, Vec::new());
+        assert!(simulator.inspired.contains(&ShipId(0)));
+        assert_eq!(simulator.game.ships[&ShipId(0)].halite, 75);
+        assert_eq!(simulator.game.map.at_position(&Position { x: 1, y: 8 }).halite, 75);
+    }
+
+    #[test]
+    fn collision_cargo_goes_to_the_cell_or_the_structure_owner() {
+        let mut game = get_game();
+        testing::add_ship(&mut game, 0, 0, Position { x: 7, y: 7 }, 200);
+        testing::add_ship(&mut game, 1, 1, Position { x: 9, y: 7 }, 300);
+        testing::add_ship(&mut game, 0, 2, Position { x: 11, y: 12 }, 200);
+        testing::add_ship(&mut game, 1, 3, Position { x: 13, y: 12 }, 300);
+
+        let (simulator, events) = play(&game, vec![
+            (0, EngineCommand::Move(ShipId(0), Direction::East)),
+            (1, EngineCommand::Move(ShipId(1), Direction::West)),
+            (0, EngineCommand::Move(ShipId(2), Direction::East)),
+            (1, EngineCommand::Move(ShipId(3), Direction::West)),
+        ]);
+        assert_eq!(events.collisions.len(), 2);
+        assert!(simulator.game.ships.is_empty());
+        assert_eq!(simulator.game.map.at_position(&Position { x: 8, y: 7 }).halite, 500);
+        assert_eq!(simulator.game.map.at_position(&Position { x: 12, y: 12 }).halite, 0);
+        assert_eq!(simulator.game.players[0].halite, 5000);
+        assert_eq!(simulator.game.players[1].halite, 5500);
+    }
+
+    #[test]
+    fn dropoff_cost_is_offset_by_cargo_and_cell() {
+        let mut game = get_game();
+        game.players[0].halite = 3600;
+        testing::add_ship(&mut game, 0, 0, Position { x: 8, y: 4 }, 300);
+        game.map.at_position_mut(&Position { x: 8, y: 4 }).halite = 200;
+
+        let (simulator, events) = play(&game, vec![(0, EngineCommand::Construct(ShipId(0)))]);
+        assert_eq!(events.constructions.len(), 1);
+        assert_eq!(simulator.game.players[0].halite, 100);
+        assert!(simulator.game.ships.is_empty());
+        let cell = simulator.game.map.at_position(&Position { x: 8, y: 4 });
+        assert_eq!(cell.halite, 0);
+        assert!(cell.structure == Structure::Dropoff(events.constructions[0]));
+    }
+
+    // Like on the server, a new ship isn't held back by a ship on the shipyard, they both sink
+    #[test]
+    fn spawn_onto_an_occupied_shipyard_collides() {
+        let mut game = get_game();
+        testing::add_ship(&mut game, 0, 0, Position { x: 4, y: 4 }, 300);
+
+        let (simulator, events) = play(&game, vec![(0, EngineCommand::Spawn)]);
+        assert_eq!(events.spawns.len(), 1);
+        assert_eq!(events.collisions.len(), 1);
+        assert!(events.collisions[0].ship_ids == vec![ShipId(0), events.spawns[0].id]);
+        assert!(simulator.game.players[0].ship_ids.is_empty());
+        assert_eq!(simulator.game.players[0].halite, 5000 - 1000 + 300);
+    }
+
+    #[test]
+    fn construct_on_a_structure_is_skipped() {
+        let mut game = get_game();
+        testing::add_ship(&mut game, 0, 0, Position { x: 4, y: 4 }, 300);
+
+        let (simulator, events) = play(&game, vec![(0, EngineCommand::Construct(ShipId(0)))]);
+        assert!(events.constructions.is_empty());
+        assert_eq!(events.failed_commands.len(), 1);
+        assert!(simulator.game.dropoffs.is_empty());
+        assert!(simulator.game.ships.contains_key(&ShipId(0)));
+        assert!(simulator.game.map.at_position(&Position { x: 4, y: 4 }).structure == Structure::Shipyard(PlayerId(0)));
+    }
+}
@@ -0,0 +1,42 @@
+use engine::mapgen::MapSettings;
+use engine::runner::get_constants_line;
+use engine::runner::MatchSettings;
+use engine::simulator::Simulator;
+use hlt::constants::Constants;
+use hlt::dropoff::Dropoff;
+use hlt::DropoffId;
+use hlt::game::Game;
+use hlt::game_map::GameMap;
+use hlt::map_cell::MapCell;
+use hlt::map_cell::Structure;
+use hlt::PlayerId;
+use hlt::position::Position;
+use hlt::ship::Ship;
+use hlt::ShipId;
+
+// Small hand-built games for the unit tests
+
+pub fn get_constants(size: usize) -> Constants {
+    Constants::new(&get_constants_line(&MatchSettings::new(0, size), &MapSettings::new()))
+}
+
+// A square map without any halite, a shipyard for every player, and no ships yet
+pub fn get_game(size: usize, shipyards: &[Position]) -> Game {
+    let cells = (0..size)
+        .map(|y| (0..size).map(|x| MapCell { position: Position { x: x as i32, y: y as i32 }, halite: 0, structure: Structure::None }).collect())
+        .collect();
+    Simulator::new(get_constants(size), GameMap { width: size, height: size, cells }, shipyards).game
+}
+
+pub fn add_ship(game: &mut Game, owner: usize, id: usize, position: Position, halite: usize) {
+    let ship = Ship::new(PlayerId(owner), ShipId(id), position, halite, game.constants.max_halite);
+    game.players[owner].ship_ids.push(ship.id);
+    game.ships.insert(ship.id, ship);
+}
+
+pub fn add_dropoff(game: &mut Game, owner: usize, id: usize, position: Position) {
+    let dropoff = Dropoff { owner: PlayerId(owner), id: DropoffId(id), position };
+    game.map.at_position_mut(&position).structure = Structure::Dropoff(dropoff.id);
+    game.players[owner].dropoff_ids.push(dropoff.id);
+    game.dropoffs.insert(dropoff.id, dropoff);
+}
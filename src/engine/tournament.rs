@@ -0,0 +1,299 @@
+use engine::runner::MatchResult;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// The map sizes the ladder plays on
+pub const LADDER_SIZES: [usize; 5] = [32, 40, 48, 56, 64];
+
+const INITIAL_RATING: f64 = 1500.0;
+const K_FACTOR: f64 = 32.0;
+
+// One game of the schedule: the entrant in each seat, seat 0 playing as player 0
+pub struct Pairing {
+    pub seed: u64,
+    pub size: usize,
+    pub seats: Vec<usize>,
+}
+
+pub struct GameRecord {
+    pub seed: u64,
+    pub size: usize,
+    pub seats: Vec<usize>,
+    pub turns: usize,
+    pub ranks: Vec<usize>,
+    pub halite: Vec<usize>,
+}
+
+// How one entrant did on one map size and player count, counting every seat it played
+pub struct ConfigurationStats {
+    pub size: usize,
+    pub num_players: usize,
+    pub entrant: usize,
+    pub games: usize,
+    pub wins: usize,
+    pub rank_sum: usize,
+    pub halite_sum: usize,
+}
+
+impl ConfigurationStats {
+    pub fn get_win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+
+    pub fn get_mean_rank(&self) -> f64 {
+        self.rank_sum as f64 / self.games.max(1) as f64
+    }
+
+    pub fn get_mean_halite(&self) -> f64 {
+        self.halite_sum as f64 / self.games.max(1) as f64
+    }
+}
+
+// Every group of entrants that plays together. With fewer entrants than seats the entrants take
+// turns filling the seats, so two entrants still get 4 player games. When the seats don't split
+// evenly every entrant gets a group where it is the one with the extra seat.
+fn get_groups(num_entrants: usize, num_players: usize) -> Vec<Vec<usize>> {
+    if num_entrants < num_players {
+        let offsets = if num_players.is_multiple_of(num_entrants) { 1 } else { num_entrants };
+        return (0..offsets)
+            .map(|offset| (0..num_players).map(|seat| (seat + offset) % num_entrants).collect())
+            .collect();
+    }
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group: Vec<usize> = (0..num_players).collect();
+    loop {
+        groups.push(group.clone());
+        // Next combination in lexicographic order
+        let mut i = num_players;
+        while i > 0 && group[i - 1] == num_entrants - num_players + i - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return groups;
+        }
+        group[i - 1] += 1;
+        for j in i..num_players {
+            group[j] = group[j - 1] + 1;
+        }
+    }
+}
+
+// Every group plays every size on the same seeds, once from each seat, so that neither the map
+// nor the starting corner favors anyone
+pub fn get_schedule(num_entrants: usize, sizes: &[usize], player_counts: &[usize], first_seed: u64, seeds: usize) -> Vec<Pairing> {
+    let mut schedule: Vec<Pairing> = Vec::new();
+    for &num_players in player_counts {
+        for &size in sizes {
+            for group in get_groups(num_entrants, num_players) {
+                for seed in first_seed..first_seed + seeds as u64 {
+                    for rotation in 0..num_players {
+                        let seats = (0..num_players).map(|seat| group[(seat + rotation) % num_players]).collect();
+                        schedule.push(Pairing { seed, size, seats });
+                    }
+                }
+            }
+        }
+    }
+    schedule
+}
+
+// Elo ratings updated game by game, where a game counts as a win against everyone ranked below
+// and a loss against everyone ranked above
+pub struct Tournament {
+    pub entrants: Vec<String>,
+    pub names: Vec<String>,
+    pub ratings: Vec<f64>,
+    pub games: Vec<GameRecord>,
+}
+
+impl Tournament {
+    pub fn new(entrants: &[String]) -> Tournament {
+        Tournament {
+            entrants: entrants.to_vec(),
+            names: entrants.to_vec(),
+            ratings: entrants.iter().map(|_| INITIAL_RATING).collect(),
+            games: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, pairing: &Pairing, result: &MatchResult) {
+        let mut ranks: Vec<usize> = pairing.seats.iter().map(|_| 0).collect();
+        let mut halite: Vec<usize> = pairing.seats.iter().map(|_| 0).collect();
+        for player in &result.players {
+            ranks[player.player_id.0] = player.rank;
+            halite[player.player_id.0] = player.halite;
+            self.names[pairing.seats[player.player_id.0]] = player.name.clone();
+        }
+
+        // Seats of the same entrant don't play each other
+        let mut changes: Vec<f64> = self.ratings.iter().map(|_| 0.0).collect();
+        let opponents = (pairing.seats.len() - 1) as f64;
+        for (seat, &entrant) in pairing.seats.iter().enumerate() {
+            for (other_seat, &other) in pairing.seats.iter().enumerate() {
+                if entrant == other {
+                    continue;
+                }
+                let expected = 1.0 / (1.0 + 10f64.powf((self.ratings[other] - self.ratings[entrant]) / 400.0));
+                let score = if ranks[seat] < ranks[other_seat] { 1.0 } else { 0.0 };
+                changes[entrant] += K_FACTOR * (score - expected) / opponents;
+            }
+        }
+        for (rating, change) in self.ratings.iter_mut().zip(changes) {
+            *rating += change;
+        }
+
+        self.games.push(GameRecord { seed: pairing.seed, size: pairing.size, seats: pairing.seats.clone(), turns: result.turns, ranks, halite });
+    }
+
+    // Sorted by player count, size and entrant
+    pub fn get_configuration_stats(&self) -> Vec<ConfigurationStats> {
+        let mut stats: HashMap<(usize, usize, usize), ConfigurationStats> = HashMap::new();
+        for game in &self.games {
+            for (seat, &entrant) in game.seats.iter().enumerate() {
+                let key = (game.seats.len(), game.size, entrant);
+                let entry = stats.entry(key).or_insert(ConfigurationStats {
+                    size: game.size,
+                    num_players: game.seats.len(),
+                    entrant,
+                    games: 0,
+                    wins: 0,
+                    rank_sum: 0,
+                    halite_sum: 0,
+                });
+                entry.games += 1;
+                if game.ranks[seat] == 1 {
+                    entry.wins += 1;
+                }
+                entry.rank_sum += game.ranks[seat];
+                entry.halite_sum += game.halite[seat];
+            }
+        }
+        let mut stats: Vec<ConfigurationStats> = stats.into_values().collect();
+        stats.sort_by_key(|entry| (entry.num_players, entry.size, entry.entrant));
+        stats
+    }
+
+    // Entrant indices from the highest rating down
+    pub fn get_standings(&self) -> Vec<usize> {
+        let mut standings: Vec<usize> = (0..self.entrants.len()).collect();
+        standings.sort_by(|a, b| self.ratings[*b].partial_cmp(&self.ratings[*a]).unwrap().then(a.cmp(b)));
+        standings
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut lines = vec!["players,size,entrant,name,rating,games,wins,win_rate,mean_rank,mean_halite".to_string()];
+        for entry in self.get_configuration_stats() {
+            lines.push(format!(
+                "{},{},\"{}\",\"{}\",{:.1},{},{},{:.3},{:.2},{:.0}",
+                entry.num_players, entry.size, self.entrants[entry.entrant].replace('"', "\"\""), self.names[entry.entrant].replace('"', "\"\""),
+                self.ratings[entry.entrant], entry.games, entry.wins, entry.get_win_rate(), entry.get_mean_rank(), entry.get_mean_halite()));
+        }
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self) -> Value {
+        let entrants: Vec<Value> = self.get_standings().iter()
+            .map(|&entrant| {
+                let games: Vec<&GameRecord> = self.games.iter().filter(|game| game.seats.contains(&entrant)).collect();
+                json!({
+                    "entrant": entrant,
+                    "command": self.entrants[entrant],
+                    "name": self.names[entrant],
+                    "rating": self.ratings[entrant],
+                    "games": games.len(),
+                })
+            })
+            .collect();
+        let configurations: Vec<Value> = self.get_configuration_stats().iter()
+            .map(|entry| json!({
+                "players": entry.num_players,
+                "size": entry.size,
+                "entrant": entry.entrant,
+                "games": entry.games,
+                "wins": entry.wins,
+                "win_rate": entry.get_win_rate(),
+                "mean_rank": entry.get_mean_rank(),
+                "mean_halite": entry.get_mean_halite(),
+            }))
+            .collect();
+        let games: Vec<Value> = self.games.iter()
+            .map(|game| json!({
+                "seed": game.seed,
+                "size": game.size,
+                "seats": game.seats,
+                "turns": game.turns,
+                "ranks": game.ranks,
+                "halite": game.halite,
+            }))
+            .collect();
+        json!({ "entrants": entrants, "configurations": configurations, "games": games })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::runner::MatchResult;
+    use engine::runner::PlayerResult;
+    use hlt::PlayerId;
+    use super::get_schedule;
+    use super::Pairing;
+    use super::Tournament;
+
+    fn get_result(ranks: &[usize]) -> MatchResult {
+        let players = ranks.iter().enumerate()
+            .map(|(id, &rank)| PlayerResult { player_id: PlayerId(id), name: format!("bot {}", id), command: String::new(), rank, halite: 0, elimination: None })
+            .collect();
+        MatchResult { seed: 1, size: 32, turns: 400, players }
+    }
+
+    #[test]
+    fn every_entrant_plays_every_seat_equally_often() {
+        for &(num_entrants, num_players) in &[(2, 2), (3, 2), (5, 2), (2, 4), (3, 4), (5, 4), (6, 4)] {
+            let schedule = get_schedule(num_entrants, &[32, 40], &[num_players], 1, 2);
+            let mut counts = vec![vec![0; num_players]; num_entrants];
+            for pairing in &schedule {
+                assert_eq!(pairing.seats.len(), num_players);
+                for (seat, &entrant) in pairing.seats.iter().enumerate() {
+                    counts[entrant][seat] += 1;
+                }
+            }
+            let expected = counts[0][0];
+            assert!(expected > 0);
+            assert!(counts.iter().flatten().all(|&count| count == expected), "{} entrants in {} player games: {:?}", num_entrants, num_players, counts);
+        }
+    }
+
+    #[test]
+    fn two_player_win_moves_the_ratings_by_half_the_k_factor() {
+        let mut tournament = Tournament::new(&["a".to_string(), "b".to_string()]);
+        tournament.record(&Pairing { seed: 1, size: 32, seats: vec![0, 1] }, &get_result(&[1, 2]));
+        assert!((tournament.ratings[0] - 1516.0).abs() < 1e-9);
+        assert!((tournament.ratings[1] - 1484.0).abs() < 1e-9);
+        assert_eq!(tournament.names, vec!["bot 0".to_string(), "bot 1".to_string()]);
+        assert_eq!(tournament.get_standings(), vec![0, 1]);
+    }
+
+    #[test]
+    fn four_player_games_score_against_every_other_seat() {
+        let entrants: Vec<String> = (0..4).map(|entrant| entrant.to_string()).collect();
+        let mut tournament = Tournament::new(&entrants);
+        tournament.ratings = vec![1600.0, 1500.0, 1500.0, 1400.0];
+        tournament.record(&Pairing { seed: 1, size: 32, seats: vec![3, 2, 1, 0] }, &get_result(&[1, 2, 3, 4]));
+
+        let expected = |rating: f64, other: f64| 1.0 / (1.0 + 10f64.powf((other - rating) / 400.0));
+        let change = 32.0 * (3.0 - expected(1400.0, 1500.0) * 2.0 - expected(1400.0, 1600.0)) / 3.0;
+        assert!((tournament.ratings[3] - (1400.0 + change)).abs() < 1e-9);
+        // Elo only moves points around
+        assert!((tournament.ratings.iter().sum::<f64>() - 6000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn seats_of_the_same_entrant_do_not_play_each_other() {
+        let mut tournament = Tournament::new(&["a".to_string(), "b".to_string()]);
+        tournament.record(&Pairing { seed: 1, size: 32, seats: vec![0, 1, 0, 1] }, &get_result(&[1, 2, 3, 4]));
+        // Entrant 0 beat entrant 1 three times out of four, over three opponents per seat
+        assert!((tournament.ratings[0] - (1500.0 + 32.0 * (3.0 - 2.0) / 3.0)).abs() < 1e-9);
+        assert!((tournament.ratings[1] - (1500.0 - 32.0 * (3.0 - 2.0) / 3.0)).abs() < 1e-9);
+    }
+}
@@ -2,10 +2,12 @@ use hlt::log::Log;
 use std::collections::HashMap;
 use std::str::FromStr;
 
+#[derive(Clone)]
 pub struct Constants {
     pub max_halite: usize,
     pub ship_cost: usize,
     pub dropoff_cost: usize,
+    pub initial_halite: usize,
     pub max_turns: usize,
     pub extract_ratio: usize,
     pub move_cost_ratio: usize,
@@ -36,6 +38,7 @@ impl Constants {
         Constants {
             ship_cost: Constants::get_value(&map, "NEW_ENTITY_ENERGY_COST"),
             dropoff_cost: Constants::get_value(&map, "DROPOFF_COST"),
+            initial_halite: Constants::get_value(&map, "INITIAL_ENERGY"),
             max_halite: Constants::get_value(&map, "MAX_ENERGY"),
             max_turns: Constants::get_value(&map, "MAX_TURNS"),
             extract_ratio: Constants::get_value(&map, "EXTRACT_RATIO"),
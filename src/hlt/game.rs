@@ -12,6 +12,7 @@ use hlt::ship::Ship;
 use hlt::ShipId;
 use std::collections::HashMap;
 
+#[derive(Clone)]
 pub struct Game {
     pub constants: Constants,
     pub turn_number: usize,
@@ -53,6 +54,20 @@ impl Game {
         }
     }
 
+    // A game that doesn't talk to a server, for running the rules locally
+    pub fn from_state(constants: Constants, my_id: PlayerId, players: Vec<Player>, map: GameMap) -> Game {
+        Game {
+            constants,
+            turn_number: 0,
+            my_id,
+            players,
+            ships: HashMap::new(),
+            dropoffs: HashMap::new(),
+            map,
+            input: Input::new(),
+        }
+    }
+
     pub fn ready(name: &str) {
         println!("{}", name);
     }
@@ -3,6 +3,7 @@ use std::io::stdin;
 use std::process::exit;
 use std::str::FromStr;
 
+#[derive(Clone)]
 pub struct Input {
     tokens: Vec<String>,
     current_token: usize,
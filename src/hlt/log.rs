@@ -12,11 +12,23 @@ lazy_static! {
 pub struct Log {
     log_buffer: Option<Vec<String>>,
     file: Option<File>,
+    mutes: usize,
+}
+
+// Keeps the log muted for as long as it's alive
+pub struct Mute {
+    _private: (),
+}
+
+impl Drop for Mute {
+    fn drop(&mut self) {
+        LOG.lock().unwrap().mutes -= 1;
+    }
 }
 
 impl Log {
     pub fn new() -> Log {
-        Log { log_buffer: Some(Vec::new()), file: None }
+        Log { log_buffer: Some(Vec::new()), file: None, mutes: 0 }
     }
 
     pub fn open(bot_id: usize) {
@@ -35,8 +47,22 @@ impl Log {
         log.log_buffer = None;
     }
 
+    // Drops every message until the last Mute is gone, for when nobody reads the log and it
+    // would only pile up
+    pub fn mute() -> Mute {
+        LOG.lock().unwrap().mutes += 1;
+        Mute { _private: () }
+    }
+
+    pub fn is_muted() -> bool {
+        LOG.lock().unwrap().mutes > 0
+    }
+
     pub fn log(message: &str) {
         let mut log = LOG.lock().unwrap();
+        if log.mutes > 0 {
+            return;
+        }
 
         match &mut log.file {
             Some(file) => {
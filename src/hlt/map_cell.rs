@@ -2,13 +2,14 @@ use hlt::DropoffId;
 use hlt::PlayerId;
 use hlt::position::Position;
 
+#[derive(Clone)]
 pub struct MapCell {
     pub position: Position,
     pub halite: usize,
     pub structure: Structure,
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub enum Structure {
     None,
     Dropoff(DropoffId),
@@ -26,6 +26,8 @@ pub mod position;
 pub mod ship;
 #[allow(dead_code)]
 pub mod shipyard;
+#[allow(dead_code)]
+pub mod traffic;
 
 #[allow(dead_code)]
 mod input;
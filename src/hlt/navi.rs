@@ -4,11 +4,31 @@ use hlt::position::Position;
 use hlt::ship::Ship;
 use hlt::ShipId;
 use hlt::game::Game;
+use hlt::traffic::Traffic;
+use rand::Rng;
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+// How many past positions we keep per ship to spot oscillations
+const HISTORY_LENGTH: usize = 6;
+// How many turns a ship can be blocked in place before we consider it stuck
+const STUCK_TURNS: usize = 3;
 
 pub struct Navi {
     pub width: usize,
     pub height: usize,
     pub occupied: Vec<Vec<Option<ShipId>>>,
+    // Cells next to enemy ships that are likely to ram us. Moves avoid them, but ships mining on
+    // one only leave when they carry enough to be worth ramming
+    pub dangerous: Vec<Vec<bool>>,
+    pub history: HashMap<ShipId, VecDeque<Position>>,
+    // Own ship that was in the way of each ship's preferred move on the previous turn
+    pub blocked_by: HashMap<ShipId, ShipId>,
+    pub prioritized: HashSet<ShipId>,
+    pub yielding: HashSet<ShipId>,
+    pub traffic: Traffic,
 }
 
 impl Navi {
@@ -18,7 +38,17 @@ impl Navi {
             occupied.push(vec![None; width]);
         }
 
-        Navi { width, height, occupied }
+        Navi {
+            width,
+            height,
+            occupied,
+            dangerous: vec![vec![false; width]; height],
+            history: HashMap::new(),
+            blocked_by: HashMap::new(),
+            prioritized: HashSet::new(),
+            yielding: HashSet::new(),
+            traffic: Traffic::new(width, height),
+        }
     }
 
     pub fn update_frame(&mut self, game: &Game) {
@@ -30,12 +60,163 @@ impl Navi {
                 self.mark_unsafe_ship(&ship);
             }
         }
+
+        self.traffic.update_frame(game);
+        self.update_history(game);
+        self.detect_stalls();
+    }
+
+    pub fn update_history(&mut self, game: &Game) {
+        let me = &game.players[game.my_id.0];
+        // Forget ships that didn't make it to this turn
+        self.history.retain(|ship_id, _| me.ship_ids.contains(ship_id));
+        self.blocked_by.retain(|ship_id, occupier| me.ship_ids.contains(ship_id) && me.ship_ids.contains(occupier));
+
+        for ship_id in &me.ship_ids {
+            let position = game.ships[ship_id].position;
+            let history = self.history.entry(*ship_id).or_default();
+            history.push_back(position);
+            if history.len() > HISTORY_LENGTH {
+                history.pop_front();
+            }
+        }
+    }
+
+    // Looks at the move history and last turn's blockers and decides which ships get to move first
+    // and which ones have to step aside this turn.
+    pub fn detect_stalls(&mut self) {
+        self.prioritized.clear();
+        self.yielding.clear();
+
+        let mut ship_ids: Vec<ShipId> = self.history.keys().cloned().collect();
+        ship_ids.sort_by_key(|ship_id| ship_id.0);
+
+        for ship_id in &ship_ids {
+            if self.is_oscillating(ship_id) {
+                Log::log(&format!("Ship {} is oscillating, raising its priority and avoiding its previous cell", ship_id.0));
+                self.prioritized.insert(*ship_id);
+            }
+        }
+
+        let mut visited: HashSet<ShipId> = HashSet::new();
+        for ship_id in &ship_ids {
+            if visited.contains(ship_id) {
+                continue;
+            }
+
+            match self.get_blocking_cycle(ship_id) {
+                Some(cycle) => {
+                    // Lowest id steps aside so the rest of the cycle can move through
+                    let yielder = *cycle.iter().min_by_key(|ship_id| ship_id.0).unwrap();
+                    let cycle_ids: Vec<String> = cycle.iter().map(|ship_id| ship_id.0.to_string()).collect();
+                    Log::log(&format!("Deadlock between ships [{}], ship {} yields", cycle_ids.join(", "), yielder.0));
+                    for cycle_ship_id in &cycle {
+                        visited.insert(*cycle_ship_id);
+                        if *cycle_ship_id == yielder {
+                            self.yielding.insert(*cycle_ship_id);
+                        } else {
+                            self.prioritized.insert(*cycle_ship_id);
+                        }
+                    }
+                },
+                None => {
+                    if self.blocked_by.contains_key(ship_id) && self.get_stuck_turns(ship_id) >= STUCK_TURNS {
+                        Log::log(&format!("Ship {} has been stuck for {} turns, raising its priority", ship_id.0, self.get_stuck_turns(ship_id)));
+                        self.prioritized.insert(*ship_id);
+                    }
+                },
+            }
+        }
+
+        self.blocked_by.clear();
+    }
+
+    // A ship oscillates when it keeps bouncing between the same two cells
+    pub fn is_oscillating(&self, ship_id: &ShipId) -> bool {
+        match self.history.get(ship_id) {
+            Some(history) if history.len() >= 4 => {
+                let len = history.len();
+                let (a, b, c, d) = (history[len - 4], history[len - 3], history[len - 2], history[len - 1]);
+                !a.equal(&b) && a.equal(&c) && b.equal(&d)
+            },
+            _ => false,
+        }
+    }
+
+    pub fn get_previous_position(&self, ship_id: &ShipId) -> Option<Position> {
+        match self.history.get(ship_id) {
+            Some(history) if history.len() >= 2 => Some(history[history.len() - 2]),
+            _ => None,
+        }
+    }
+
+    pub fn get_stuck_turns(&self, ship_id: &ShipId) -> usize {
+        match self.history.get(ship_id) {
+            Some(history) => {
+                let current = history[history.len() - 1];
+                history.iter().rev().skip(1).take_while(|position| position.equal(&current)).count()
+            },
+            None => 0,
+        }
+    }
+
+    // Follows the "blocked by" chain starting at the ship and returns it if it leads back to the ship
+    pub fn get_blocking_cycle(&self, ship_id: &ShipId) -> Option<Vec<ShipId>> {
+        let mut cycle: Vec<ShipId> = vec![*ship_id];
+        let mut current = *ship_id;
+        while let Some(next) = self.blocked_by.get(&current) {
+            if *next == *ship_id {
+                return if cycle.len() > 1 { Some(cycle) } else { None };
+            }
+            if cycle.contains(next) {
+                return None;
+            }
+            cycle.push(*next);
+            current = *next;
+        }
+        None
+    }
+
+    // Ships that have been stuck or oscillating get to pick their moves first
+    pub fn get_priority(&self, ship_id: &ShipId) -> u8 {
+        if self.prioritized.contains(ship_id) {
+            0
+        } else if self.yielding.contains(ship_id) {
+            2
+        } else {
+            1
+        }
+    }
+
+    pub fn is_yielding(&self, ship_id: &ShipId) -> bool {
+        self.yielding.contains(ship_id)
+    }
+
+    // Oscillating ships are not allowed to step back into the cell they just left
+    pub fn is_backtracking(&self, ship_id: &ShipId, position: &Position) -> bool {
+        if !self.is_oscillating(ship_id) {
+            return false;
+        }
+        match self.get_previous_position(ship_id) {
+            Some(previous_position) => self.normalize(&previous_position).equal(&self.normalize(position)),
+            None => false,
+        }
+    }
+
+    pub fn record_blocker(&mut self, ship_id: ShipId, target_pos: &Position, owner_ships: &[ShipId]) {
+        let target_pos = self.normalize(target_pos);
+        if let Some(occupier) = self.occupied[target_pos.y as usize][target_pos.x as usize] {
+            if occupier != ship_id && owner_ships.contains(&occupier) {
+                self.blocked_by.insert(ship_id, occupier);
+            }
+        }
     }
 
     pub fn clear(&mut self) {
         for y in 0..self.height {
             for x in 0..self.width {
                 self.occupied[y][x] = None;
+                self.dangerous[y][x] = false;
             }
         }
     }
@@ -75,6 +256,16 @@ impl Navi {
         is_safe_from_own_ships
     }
 
+    pub fn mark_dangerous(&mut self, position: &Position) {
+        let position = self.normalize(position);
+        self.dangerous[position.y as usize][position.x as usize] = true;
+    }
+
+    pub fn is_dangerous(&self, position: &Position) -> bool {
+        let position = self.normalize(position);
+        self.dangerous[position.y as usize][position.x as usize]
+    }
+
     pub fn is_unsafe(&self, position: &Position) -> bool {
         !self.is_safe(position)
     }
@@ -138,46 +329,85 @@ impl Navi {
         possible_moves
     }
 
-    pub fn better_navigate(&mut self, ship: &Ship, destination: &Position, owner_ships: &Vec<ShipId>, future_positions: &Vec<Position>, current_positions: &Vec<Position>) -> Direction {
+    pub fn better_navigate(&mut self, ship: &Ship, destination: &Position, owner_ships: &Vec<ShipId>, future_positions: &Vec<Position>, current_positions: &Vec<Position>, rng: &mut StdRng) -> Direction {
         let ship_position = &ship.position;
+        // Ships heading to a base go through its inbound lane
+        let is_returning = self.traffic.is_base(destination);
+        let destination = &self.traffic.get_approach(ship_position, destination);
         // You can only ever have 1 or 2 possible moves. O possible moves means you've reached your destination.
-        let possible_moves = self.get_unsafe_moves(&ship_position, destination);
+        let mut possible_moves = self.get_unsafe_moves(ship_position, destination);
+        // Both moves get us closer, so which one we try first is a coin flip,
+        // unless one of them puts our cargo next to someone who likes to ram
+        rng.shuffle(&mut possible_moves);
+        if ship.halite > 0 {
+            possible_moves.sort_by_key(|direction| self.is_dangerous(&ship_position.directional_offset(*direction)));
+        }
+
+        if self.is_yielding(&ship.id) {
+            // Step aside to a cell that is not on the way to the destination to let the others through
+            let mut sidesteps = Direction::get_all_cardinals();
+            rng.shuffle(&mut sidesteps);
+            for direction in sidesteps {
+                let target_pos = ship_position.directional_offset(direction);
+                if !possible_moves.contains(&direction) && self.is_smart_safe(&target_pos, &ship.position, owner_ships, future_positions, current_positions) {
+                    Log::log(&format!("Ship {} yields by moving {}", ship.id.0, direction.get_char_encoding()));
+                    self.mark_unsafe(&target_pos, ship.id);
+                    return direction;
+                }
+            }
+            Log::log(&format!("Ship {} yields by staying still", ship.id.0));
+            return Direction::Still;
+        }
 
         // get_unsafe_moves normalizes for us
+        let mut is_waiting = false;
         for direction in &possible_moves  {
             let target_pos = ship_position.directional_offset(*direction);
 
+            if is_returning && !self.traffic.may_enter(&ship.id, &target_pos) {
+                is_waiting = true;
+                continue;
+            }
+
+            if self.is_backtracking(&ship.id, &target_pos) {
+                Log::log(&format!("Ship {} avoids moving back to {}, {}", ship.id.0, target_pos.x, target_pos.y));
+                continue;
+            }
+
+            if !self.traffic.is_allowed(&target_pos, is_returning) {
+                Log::log(&format!("Ship {} keeps out of the base lane at {}, {}", ship.id.0, target_pos.x, target_pos.y));
+                continue;
+            }
+
             if self.is_smart_safe(&target_pos, &ship.position, owner_ships, future_positions, current_positions) {
                 self.mark_unsafe(&target_pos, ship.id);
                 return *direction;
             }
         }
 
-        // Get the first possible move and check whether to go move out of the way vertically or horizontally.
-        match possible_moves.first() {
-          Some(possible_move) => {
-            match possible_move {
-              Direction::North | Direction::South => {
-                if self.is_smart_safe(&ship_position.directional_offset(Direction::West), &ship.position, owner_ships, future_positions, current_positions) {
-                  Direction::West
-                } else {
-                  Direction::East
-                }
-              },
-              Direction::West | Direction::East => {
-                if self.is_smart_safe(&ship_position.directional_offset(Direction::North), &ship.position, owner_ships, future_positions, current_positions) {
-                  Direction::North
-                } else {
-                  Direction::South
-                }
-              },
-              // This should never happen
-              Direction::Still => Direction::Still
+        if is_waiting {
+            Log::log(&format!("Ship {} waits its turn to enter the base", ship.id.0));
+            return Direction::Still;
+        }
+
+        if let Some(direction) = possible_moves.first() {
+            self.record_blocker(ship.id, &ship_position.directional_offset(*direction), owner_ships);
+        }
+
+        // Step out of the way sideways, or wait where we are when neither side is free either
+        let sidesteps = match possible_moves.first() {
+            Some(Direction::North) | Some(Direction::South) => vec![Direction::West, Direction::East],
+            Some(Direction::West) | Some(Direction::East) => vec![Direction::North, Direction::South],
+            _ => Vec::new(),
+        };
+        for direction in sidesteps {
+            let target_pos = ship_position.directional_offset(direction);
+            if self.is_smart_safe(&target_pos, &ship.position, owner_ships, future_positions, current_positions) {
+                self.mark_unsafe(&target_pos, ship.id);
+                return direction;
             }
-          },
-          // This should never happen
-          None => Direction::Still,
         }
+        Direction::Still
     }
 
     pub fn naive_navigate(&mut self, ship: &Ship, destination: &Position) -> Direction {
@@ -204,3 +434,89 @@ impl Navi {
         Position { x, y }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::direction::Direction;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use super::Navi;
+
+    fn set_history(navi: &mut Navi, ship_id: usize, cells: &[(i32, i32)]) {
+        navi.history.insert(ShipId(ship_id), cells.iter().map(|&(x, y)| Position { x, y }).collect());
+    }
+
+    #[test]
+    fn oscillating_ship_gets_priority_and_cannot_step_back() {
+        let mut navi = Navi::new(8, 8);
+        set_history(&mut navi, 0, &[(1, 1), (2, 1), (1, 1), (2, 1)]);
+        set_history(&mut navi, 1, &[(5, 4), (5, 5), (5, 6), (5, 7)]);
+        navi.detect_stalls();
+
+        assert!(navi.is_oscillating(&ShipId(0)));
+        assert!(!navi.is_oscillating(&ShipId(1)));
+        assert_eq!(navi.get_priority(&ShipId(0)), 0);
+        assert_eq!(navi.get_priority(&ShipId(1)), 1);
+        assert!(navi.is_backtracking(&ShipId(0), &Position { x: 1, y: 1 }));
+        assert!(!navi.is_backtracking(&ShipId(0), &Position { x: 3, y: 1 }));
+    }
+
+    #[test]
+    fn lowest_id_in_a_deadlock_yields() {
+        let mut navi = Navi::new(8, 8);
+        set_history(&mut navi, 4, &[(1, 1), (1, 1)]);
+        set_history(&mut navi, 2, &[(2, 1), (2, 1)]);
+        set_history(&mut navi, 7, &[(2, 2), (2, 2)]);
+        navi.blocked_by.insert(ShipId(4), ShipId(2));
+        navi.blocked_by.insert(ShipId(2), ShipId(7));
+        navi.blocked_by.insert(ShipId(7), ShipId(4));
+        navi.detect_stalls();
+
+        assert!(navi.is_yielding(&ShipId(2)));
+        assert_eq!(navi.get_priority(&ShipId(2)), 2);
+        assert_eq!(navi.get_priority(&ShipId(4)), 0);
+        assert_eq!(navi.get_priority(&ShipId(7)), 0);
+        // Blockers are only remembered for one turn
+        assert!(navi.blocked_by.is_empty());
+    }
+
+    #[test]
+    fn ship_blocked_for_several_turns_gets_priority() {
+        let mut navi = Navi::new(8, 8);
+        set_history(&mut navi, 0, &[(3, 3), (3, 3), (3, 3), (3, 3)]);
+        set_history(&mut navi, 1, &[(3, 3), (3, 3), (3, 3)]);
+        navi.blocked_by.insert(ShipId(0), ShipId(5));
+        navi.blocked_by.insert(ShipId(1), ShipId(5));
+        navi.detect_stalls();
+
+        assert_eq!(navi.get_stuck_turns(&ShipId(0)), 3);
+        assert_eq!(navi.get_priority(&ShipId(0)), 0);
+        // Two turns in place is not stuck yet
+        assert_eq!(navi.get_priority(&ShipId(1)), 1);
+    }
+
+    #[test]
+    fn blocked_ship_waits_without_taking_a_cell() {
+        let mut game = testing::get_game(16, &[Position { x: 12, y: 12 }, Position { x: 0, y: 0 }]);
+        testing::add_ship(&mut game, 0, 0, Position { x: 5, y: 5 }, 0);
+        let mut navi = Navi::new(16, 16);
+        navi.update_frame(&game);
+        let ship = &game.ships[&ShipId(0)];
+        // Our ships that already moved take the cell ahead and both cells to the side
+        let future_positions = vec![Position { x: 5, y: 6 }, Position { x: 4, y: 5 }, Position { x: 6, y: 5 }];
+        let current_positions = vec![Position { x: 5, y: 7 }, Position { x: 3, y: 5 }, Position { x: 7, y: 5 }];
+        let direction = navi.better_navigate(ship, &Position { x: 5, y: 9 }, &vec![ShipId(0)], &future_positions, &current_positions, &mut StdRng::seed_from_u64(0));
+        assert!(direction == Direction::Still);
+        for position in &future_positions {
+            assert!(navi.is_safe(position));
+        }
+
+        // With a side free it steps out of the way and takes that cell
+        let direction = navi.better_navigate(ship, &Position { x: 5, y: 9 }, &vec![ShipId(0)], &future_positions[..2].to_vec(), &current_positions[..2].to_vec(), &mut StdRng::seed_from_u64(0));
+        assert!(direction == Direction::East);
+        assert!(navi.occupied[5][6] == Some(ShipId(0)));
+    }
+}
@@ -8,6 +8,7 @@ use hlt::ShipId;
 use hlt::shipyard::Shipyard;
 use std::collections::HashMap;
 
+#[derive(Clone)]
 pub struct Player {
     pub id: PlayerId,
     pub shipyard: Shipyard,
@@ -17,6 +18,11 @@ pub struct Player {
 }
 
 impl Player {
+    pub fn new(id: PlayerId, shipyard_position: Position, halite: usize) -> Player {
+        let shipyard = Shipyard { owner: id, position: shipyard_position };
+        Player { id, shipyard, halite, ship_ids: Vec::new(), dropoff_ids: Vec::new() }
+    }
+
     pub fn update(
         &mut self,
         input: &mut Input,
@@ -6,6 +6,7 @@ use hlt::PlayerId;
 use hlt::position::Position;
 use hlt::ShipId;
 
+#[derive(Clone)]
 pub struct Ship {
     pub owner: PlayerId,
     pub id: ShipId,
@@ -15,6 +16,10 @@ pub struct Ship {
 }
 
 impl Ship {
+    pub fn new(owner: PlayerId, id: ShipId, position: Position, halite: usize, max_halite: usize) -> Ship {
+        Ship { owner, id, position, halite, max_halite }
+    }
+
     pub fn is_full(&self) -> bool {
         self.halite >= self.max_halite
     }
@@ -0,0 +1,260 @@
+use hlt::game::Game;
+use hlt::direction::Direction;
+use hlt::position::Position;
+use hlt::ship::Ship;
+use hlt::ShipId;
+use std::cmp::min;
+
+// Ships within this distance of a base follow its lanes
+const LANE_RADIUS: usize = 4;
+// Returning ships allowed into a base's inbound lane at once, one per inbound cell
+const ENTRY_SLOTS: usize = 2;
+
+// Each base gets fixed lanes: ships come in through the north and south cells
+// and leave through the east and west cells so they never meet head on.
+// Returning ships close to the base queue up to enter, the rest hold until
+// their turn instead of crowding the inbound cells.
+pub struct Base {
+    pub position: Position,
+    pub inbound: Vec<Position>,
+    pub outbound: Vec<Position>,
+    pub queue: Vec<ShipId>,
+}
+
+pub struct Traffic {
+    width: usize,
+    height: usize,
+    pub bases: Vec<Base>,
+}
+
+impl Traffic {
+    pub fn new(width: usize, height: usize) -> Traffic {
+        Traffic { width, height, bases: Vec::new() }
+    }
+
+    pub fn update_frame(&mut self, game: &Game) {
+        let me = &game.players[game.my_id.0];
+        let mut base_positions: Vec<Position> = vec![me.shipyard.position];
+        for dropoff_id in &me.dropoff_ids {
+            base_positions.push(game.dropoffs[dropoff_id].position);
+        }
+
+        self.bases.clear();
+        for base_position in base_positions {
+            let position = self.normalize(&base_position);
+            let inbound = vec![
+                self.normalize(&position.directional_offset(Direction::North)),
+                self.normalize(&position.directional_offset(Direction::South)),
+            ];
+            let outbound = vec![
+                self.normalize(&position.directional_offset(Direction::East)),
+                self.normalize(&position.directional_offset(Direction::West)),
+            ];
+            self.bases.push(Base { position, inbound, outbound, queue: Vec::new() });
+        }
+    }
+
+    // Lines up the returning ships close to each base: nearest first, then the fullest
+    pub fn update_queues(&mut self, returning_ships: &[&Ship]) {
+        for base in &mut self.bases {
+            base.queue.clear();
+        }
+        let mut waiting: Vec<(usize, usize, usize, usize)> = Vec::new();
+        for ship in returning_ships {
+            let index = match self.get_nearest_base_index(&ship.position) {
+                Some(index) => index,
+                None => continue,
+            };
+            let distance = self.calculate_distance(&ship.position, &self.bases[index].position);
+            if distance > 0 && distance <= LANE_RADIUS {
+                waiting.push((distance, usize::MAX - ship.halite, ship.id.0, index));
+            }
+        }
+        waiting.sort();
+        for (_, _, ship_id, index) in waiting {
+            self.bases[index].queue.push(ShipId(ship_id));
+        }
+    }
+
+    pub fn is_base(&self, position: &Position) -> bool {
+        let position = self.normalize(position);
+        self.bases.iter().any(|base| base.position.equal(&position))
+    }
+
+    pub fn is_inbound_cell(&self, position: &Position) -> bool {
+        let position = self.normalize(position);
+        self.bases.iter().any(|base| base.inbound.iter().any(|cell| cell.equal(&position)))
+    }
+
+    pub fn is_outbound_cell(&self, position: &Position) -> bool {
+        let position = self.normalize(position);
+        self.bases.iter().any(|base| base.outbound.iter().any(|cell| cell.equal(&position)))
+    }
+
+    pub fn is_lane_cell(&self, position: &Position) -> bool {
+        self.is_inbound_cell(position) || self.is_outbound_cell(position)
+    }
+
+    fn get_nearest_base_index(&self, position: &Position) -> Option<usize> {
+        (0..self.bases.len()).min_by_key(|&index| self.calculate_distance(position, &self.bases[index].position))
+    }
+
+    pub fn get_nearest_base(&self, position: &Position) -> Option<&Base> {
+        self.get_nearest_base_index(position).map(|index| &self.bases[index])
+    }
+
+    pub fn is_near_base(&self, position: &Position) -> bool {
+        match self.get_nearest_base(position) {
+            Some(base) => self.calculate_distance(position, &base.position) <= LANE_RADIUS,
+            None => false,
+        }
+    }
+
+    // Returning ships stay off the outbound cells until they are right next to the base,
+    // and outbound ships never use the inbound cells or cut back through a base.
+    pub fn is_allowed(&self, target: &Position, is_returning: bool) -> bool {
+        if is_returning {
+            self.is_base(target) || !self.is_outbound_cell(target)
+        } else {
+            !(self.is_base(target) || self.is_inbound_cell(target))
+        }
+    }
+
+    // Only the front of a base's queue may step onto its inbound cells or into the base,
+    // ships further back wait where they are
+    pub fn may_enter(&self, ship_id: &ShipId, target: &Position) -> bool {
+        let target = self.normalize(target);
+        let base = self.bases.iter().find(|base| {
+            base.position.equal(&target) || base.inbound.iter().any(|cell| cell.equal(&target))
+        });
+        match base.and_then(|base| base.queue.iter().position(|queued_id| queued_id == ship_id)) {
+            Some(place) => place < ENTRY_SLOTS,
+            None => true,
+        }
+    }
+
+    // Returning ships close to a base first head for the nearest inbound cell before going in
+    pub fn get_approach(&self, position: &Position, destination: &Position) -> Position {
+        let position = self.normalize(position);
+        let destination = self.normalize(destination);
+        let base = match self.bases.iter().find(|base| base.position.equal(&destination)) {
+            Some(base) => base,
+            None => return destination,
+        };
+
+        let distance = self.calculate_distance(&position, &base.position);
+        if distance <= 1 || distance > LANE_RADIUS {
+            return destination;
+        }
+
+        *base.inbound.iter().min_by_key(|cell| self.calculate_distance(&position, cell)).unwrap_or(&destination)
+    }
+
+    // Lower goes first: queued ships in the order they enter their base, then everybody else
+    pub fn get_priority(&self, ship_id: &ShipId) -> (u8, usize) {
+        for base in &self.bases {
+            if let Some(place) = base.queue.iter().position(|queued_id| queued_id == ship_id) {
+                return (0, place);
+            }
+        }
+        (1, 0)
+    }
+
+    fn calculate_distance(&self, source: &Position, target: &Position) -> usize {
+        let source = self.normalize(source);
+        let target = self.normalize(target);
+        let dx = (source.x - target.x).unsigned_abs() as usize;
+        let dy = (source.y - target.y).unsigned_abs() as usize;
+        min(dx, self.width - dx) + min(dy, self.height - dy)
+    }
+
+    fn normalize(&self, position: &Position) -> Position {
+        let width = self.width as i32;
+        let height = self.height as i32;
+        let x = ((position.x % width) + width) % width;
+        let y = ((position.y % height) + height) % height;
+        Position { x, y }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::position::Position;
+    use hlt::ship::Ship;
+    use hlt::ShipId;
+    use super::Traffic;
+
+    fn get_traffic(ships: &[(usize, Position, usize)]) -> (Traffic, Vec<Ship>) {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        for &(id, position, halite) in ships {
+            testing::add_ship(&mut game, 0, id, position, halite);
+        }
+        let mut traffic = Traffic::new(16, 16);
+        traffic.update_frame(&game);
+        let mut ships: Vec<Ship> = game.ships.values().cloned().collect();
+        ships.sort_by_key(|ship| ship.id.0);
+        (traffic, ships)
+    }
+
+    #[test]
+    fn ships_come_in_north_and_south_and_leave_east_and_west() {
+        let (traffic, _) = get_traffic(&[]);
+        assert!(traffic.is_base(&Position { x: 4, y: 4 }));
+        assert!(!traffic.is_base(&Position { x: 12, y: 12 }));
+        assert!(traffic.is_inbound_cell(&Position { x: 4, y: 3 }));
+        assert!(traffic.is_inbound_cell(&Position { x: 4, y: 5 }));
+        assert!(traffic.is_outbound_cell(&Position { x: 5, y: 4 }));
+        assert!(traffic.is_outbound_cell(&Position { x: 3, y: 4 }));
+        assert!(!traffic.is_lane_cell(&Position { x: 5, y: 5 }));
+        // Lanes wrap around the edges of the map like everything else
+        assert!(traffic.is_inbound_cell(&Position { x: 4, y: 19 }));
+
+        assert!(traffic.is_allowed(&Position { x: 4, y: 3 }, true));
+        assert!(traffic.is_allowed(&Position { x: 4, y: 4 }, true));
+        assert!(!traffic.is_allowed(&Position { x: 5, y: 4 }, true));
+        assert!(traffic.is_allowed(&Position { x: 5, y: 4 }, false));
+        assert!(!traffic.is_allowed(&Position { x: 4, y: 3 }, false));
+        assert!(!traffic.is_allowed(&Position { x: 4, y: 4 }, false));
+        assert!(traffic.is_allowed(&Position { x: 6, y: 6 }, false));
+    }
+
+    #[test]
+    fn nearest_and_fullest_ships_enter_first() {
+        let (mut traffic, ships) = get_traffic(&[
+            (0, Position { x: 4, y: 7 }, 900),
+            (1, Position { x: 4, y: 2 }, 500),
+            (2, Position { x: 6, y: 4 }, 800),
+            (3, Position { x: 4, y: 1 }, 1000),
+            (4, Position { x: 4, y: 12 }, 1000),
+        ]);
+        let ships: Vec<&Ship> = ships.iter().collect();
+        traffic.update_queues(&ships);
+
+        let queue: Vec<usize> = traffic.bases[0].queue.iter().map(|ship_id| ship_id.0).collect();
+        assert_eq!(queue, vec![2, 1, 3, 0]);
+        assert_eq!(traffic.get_priority(&ShipId(2)), (0, 0));
+        assert_eq!(traffic.get_priority(&ShipId(0)), (0, 3));
+        // Too far away to queue
+        assert_eq!(traffic.get_priority(&ShipId(4)), (1, 0));
+
+        assert!(traffic.may_enter(&ShipId(2), &Position { x: 4, y: 3 }));
+        assert!(traffic.may_enter(&ShipId(1), &Position { x: 4, y: 3 }));
+        assert!(!traffic.may_enter(&ShipId(3), &Position { x: 4, y: 3 }));
+        assert!(!traffic.may_enter(&ShipId(0), &Position { x: 4, y: 4 }));
+        // Away from the base nobody has to wait
+        assert!(traffic.may_enter(&ShipId(0), &Position { x: 4, y: 8 }));
+    }
+
+    #[test]
+    fn close_returning_ships_aim_for_the_nearest_inbound_cell() {
+        let (traffic, _) = get_traffic(&[]);
+        let base = Position { x: 4, y: 4 };
+        assert!(traffic.get_approach(&Position { x: 6, y: 6 }, &base).equal(&Position { x: 4, y: 5 }));
+        assert!(traffic.get_approach(&Position { x: 5, y: 1 }, &base).equal(&Position { x: 4, y: 3 }));
+        assert!(traffic.get_approach(&Position { x: 4, y: 3 }, &base).equal(&base));
+        assert!(traffic.get_approach(&Position { x: 12, y: 4 }, &base).equal(&base));
+        let target = Position { x: 6, y: 6 };
+        assert!(traffic.get_approach(&Position { x: 5, y: 5 }, &target).equal(&target));
+    }
+}
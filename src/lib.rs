@@ -0,0 +1,13 @@
+#![allow(clippy::new_without_default)]
+
+#[macro_use]
+extern crate lazy_static;
+extern crate rand;
+#[macro_use]
+extern crate serde_json;
+extern crate zstd;
+
+pub mod engine;
+pub mod hlt;
+pub mod replay;
+pub mod strategy;
@@ -0,0 +1,3 @@
+pub mod reader;
+pub mod writer;
+pub mod transcript;
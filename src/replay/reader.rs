@@ -0,0 +1,386 @@
+use hlt::command::Command;
+use hlt::constants::Constants;
+use hlt::direction::Direction;
+use hlt::dropoff::Dropoff;
+use hlt::DropoffId;
+use hlt::game::Game;
+use hlt::game_map::GameMap;
+use hlt::map_cell::MapCell;
+use hlt::map_cell::Structure;
+use hlt::player::Player;
+use hlt::PlayerId;
+use hlt::position::Position;
+use hlt::ship::Ship;
+use hlt::ShipId;
+use serde_json;
+use serde_json::value::RawValue;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use zstd;
+
+pub enum ReplayEvent {
+    Spawn { ship_id: ShipId, owner: PlayerId, position: Position },
+    Construct { dropoff_id: DropoffId, owner: PlayerId, position: Position },
+    Shipwreck { ship_ids: Vec<ShipId>, position: Position },
+}
+
+// One turn of a replay. The ships, players and map updates are what the bots got in the frame at
+// the start of the turn; the moves are what they answered and the events what came out of it.
+pub struct ReplayFrame {
+    pub turn_number: usize,
+    pub players: Vec<Player>,
+    pub ships: HashMap<ShipId, Ship>,
+    pub dropoffs: HashMap<DropoffId, Dropoff>,
+    pub cell_updates: Vec<(Position, usize)>,
+    pub moves: HashMap<PlayerId, Vec<Command>>,
+    pub events: Vec<ReplayEvent>,
+    // Halite each player has deposited since the start of the game
+    pub deposited: Vec<usize>,
+}
+
+pub struct Replay {
+    // The constants exactly as the server sent them
+    pub constants_line: String,
+    pub constants: Constants,
+    pub map_seed: u64,
+    pub player_names: Vec<String>,
+    pub initial_map: GameMap,
+    pub initial_players: Vec<Player>,
+    pub frames: Vec<ReplayFrame>,
+    // Final rank of each player, 1 for the winner, when the replay has statistics
+    pub ranks: Vec<Option<usize>>,
+}
+
+fn get<'a>(value: &'a Value, key: &str) -> Result<&'a Value, String> {
+    value.get(key).ok_or_else(|| format!("missing '{}'", key))
+}
+
+fn get_usize(value: &Value, key: &str) -> Result<usize, String> {
+    get(value, key)?.as_u64().map(|number| number as usize).ok_or_else(|| format!("'{}' is not a number", key))
+}
+
+fn get_position(value: &Value) -> Result<Position, String> {
+    Ok(Position { x: get_usize(value, "x")? as i32, y: get_usize(value, "y")? as i32 })
+}
+
+// Object keys in replays are ids written as strings
+fn parse_id(key: &str) -> Result<usize, String> {
+    key.parse().map_err(|_| format!("invalid id '{}'", key))
+}
+
+fn parse_event(value: &Value) -> Result<Option<ReplayEvent>, String> {
+    let event = match get(value, "type")?.as_str() {
+        Some("spawn") => ReplayEvent::Spawn {
+            ship_id: ShipId(get_usize(value, "id")?),
+            owner: PlayerId(get_usize(value, "owner_id")?),
+            position: get_position(get(value, "location")?)?,
+        },
+        Some("construct") => ReplayEvent::Construct {
+            dropoff_id: DropoffId(get_usize(value, "id")?),
+            owner: PlayerId(get_usize(value, "owner_id")?),
+            position: get_position(get(value, "location")?)?,
+        },
+        Some("shipwreck") => {
+            let ship_ids = get(value, "ships")?.as_array().ok_or("'ships' is not a list")?
+                .iter()
+                .map(|ship_id| ship_id.as_u64().map(|id| ShipId(id as usize)).ok_or("invalid ship id".to_string()))
+                .collect::<Result<Vec<ShipId>, String>>()?;
+            ReplayEvent::Shipwreck { ship_ids, position: get_position(get(value, "location")?)? }
+        },
+        // Anything else the viewer knows about doesn't change the state
+        _ => return Ok(None),
+    };
+    Ok(Some(event))
+}
+
+fn parse_move(value: &Value) -> Result<Command, String> {
+    match get(value, "type")?.as_str() {
+        Some("g") => Ok(Command::spawn_ship()),
+        Some("c") => Ok(Command::transform_ship_into_dropoff_site(ShipId(get_usize(value, "id")?))),
+        Some("m") => {
+            let direction = get(value, "direction")?.as_str()
+                .and_then(|direction| direction.chars().next())
+                .and_then(Direction::from_char_encoding)
+                .ok_or("invalid direction")?;
+            Ok(Command::move_ship(ShipId(get_usize(value, "id")?), direction))
+        },
+        _ => Err("unknown move type".to_string()),
+    }
+}
+
+impl Replay {
+    // Reads a .hlt file, zstd compressed or plain JSON
+    pub fn read(path: &str) -> Result<Replay, String> {
+        let bytes = fs::read(path).map_err(|error| format!("can't read {}: {}", path, error))?;
+        let json = if bytes.first() == Some(&b'{') {
+            bytes
+        } else {
+            zstd::decode_all(bytes.as_slice()).map_err(|error| format!("can't decompress {}: {}", path, error))?
+        };
+        let text = String::from_utf8(json).map_err(|_| format!("{} isn't text", path))?;
+        Replay::parse(&text).map_err(|error| format!("{}: {}", path, error))
+    }
+
+    pub fn parse(text: &str) -> Result<Replay, String> {
+        // The bots got the constants as they are written in the file, so they're kept as text too
+        let raw: HashMap<String, Box<RawValue>> = serde_json::from_str(text).map_err(|error| format!("can't parse: {}", error))?;
+        let constants_line = raw.get("GAME_CONSTANTS").ok_or("missing 'GAME_CONSTANTS'")?.get().to_string();
+        let constants = Constants::new(&constants_line);
+        let value: Value = serde_json::from_str(text).map_err(|error| format!("can't parse: {}", error))?;
+        let value = &value;
+        let map_seed = value.get("map_generator_seed").and_then(|seed| seed.as_u64()).unwrap_or(0);
+
+        let mut player_names: Vec<String> = Vec::new();
+        let mut initial_players: Vec<Player> = Vec::new();
+        let mut players_value: Vec<&Value> = get(value, "players")?.as_array().ok_or("'players' is not a list")?.iter().collect();
+        players_value.sort_by_key(|player| player.get("player_id").and_then(|id| id.as_u64()));
+        for player in players_value {
+            let id = PlayerId(get_usize(player, "player_id")?);
+            // Players are looked up by id everywhere, so the ids have to count up from 0
+            if id.0 != initial_players.len() {
+                return Err(format!("expected player {} but got player {}", initial_players.len(), id.0));
+            }
+            player_names.push(player.get("name").and_then(|name| name.as_str()).unwrap_or("").to_string());
+            initial_players.push(Player::new(id, get_position(get(player, "factory_location")?)?, constants.initial_halite));
+        }
+
+        let production_map = get(value, "production_map")?;
+        let width = get_usize(production_map, "width")?;
+        let height = get_usize(production_map, "height")?;
+        let grid = get(production_map, "grid")?.as_array().ok_or("'grid' is not a list")?;
+        let mut cells: Vec<Vec<MapCell>> = Vec::with_capacity(height);
+        for (y, row) in grid.iter().enumerate().take(height) {
+            let row = row.as_array().ok_or("grid row is not a list")?;
+            let mut cell_row: Vec<MapCell> = Vec::with_capacity(width);
+            for (x, cell) in row.iter().enumerate().take(width) {
+                let position = Position { x: x as i32, y: y as i32 };
+                cell_row.push(MapCell { position, halite: get_usize(cell, "energy")?, structure: Structure::None });
+            }
+            cells.push(cell_row);
+        }
+        let initial_map = GameMap { width, height, cells };
+
+        let mut ranks: Vec<Option<usize>> = initial_players.iter().map(|_| None).collect();
+        if let Some(statistics) = value.get("game_statistics").and_then(|statistics| statistics.get("player_statistics")).and_then(|statistics| statistics.as_array()) {
+            for player in statistics {
+                let player_id = get_usize(player, "player_id")?;
+                let rank = ranks.get_mut(player_id).ok_or_else(|| format!("statistics for unknown player {}", player_id))?;
+                *rank = Some(get_usize(player, "rank")?);
+            }
+        }
+
+        let frames = Replay::parse_frames(get(value, "full_frames")?, &initial_players, &constants)?;
+        Ok(Replay { constants_line, constants, map_seed, player_names, initial_map, initial_players, frames, ranks })
+    }
+
+    // The first of the full frames only holds the starting state, every later one is a turn. Its
+    // entities are where the ships were when the turn started, while the cells, energy and
+    // events show what the turn changed, so those only reach the bots in the next frame.
+    fn parse_frames(value: &Value, initial_players: &[Player], constants: &Constants) -> Result<Vec<ReplayFrame>, String> {
+        let full_frames = value.as_array().ok_or("'full_frames' is not a list")?;
+        let mut frames: Vec<ReplayFrame> = Vec::new();
+        let mut players: Vec<Player> = initial_players.to_vec();
+        let mut dropoffs: HashMap<DropoffId, Dropoff> = HashMap::new();
+        let mut cell_updates: Vec<(Position, usize)> = Vec::new();
+
+        for (turn_number, frame) in full_frames.iter().enumerate().skip(1) {
+            let mut ships: HashMap<ShipId, Ship> = HashMap::new();
+            for player in &mut players {
+                player.ship_ids.clear();
+            }
+            if let Some(entities) = frame.get("entities").and_then(|entities| entities.as_object()) {
+                for (player_key, player_ships) in entities {
+                    let owner = PlayerId(parse_id(player_key)?);
+                    let player = players.get_mut(owner.0).ok_or_else(|| format!("turn {} has ships of unknown player {}", turn_number, owner.0))?;
+                    for (ship_key, ship) in player_ships.as_object().ok_or("entities are not an object")? {
+                        let ship_id = ShipId(parse_id(ship_key)?);
+                        ships.insert(ship_id, Ship::new(owner, ship_id, get_position(ship)?, get_usize(ship, "energy")?, constants.max_halite));
+                        player.ship_ids.push(ship_id);
+                    }
+                }
+            }
+            for player in &mut players {
+                player.ship_ids.sort_by_key(|ship_id| ship_id.0);
+                player.dropoff_ids = dropoffs.values().filter(|dropoff| dropoff.owner == player.id).map(|dropoff| dropoff.id).collect();
+                player.dropoff_ids.sort_by_key(|dropoff_id| dropoff_id.0);
+            }
+
+            let mut moves: HashMap<PlayerId, Vec<Command>> = HashMap::new();
+            if let Some(frame_moves) = frame.get("moves").and_then(|moves| moves.as_object()) {
+                for (player_key, player_moves) in frame_moves {
+                    let commands = player_moves.as_array().ok_or("moves are not a list")?
+                        .iter()
+                        .map(parse_move)
+                        .collect::<Result<Vec<Command>, String>>()?;
+                    moves.insert(PlayerId(parse_id(player_key)?), commands);
+                }
+            }
+
+            let mut events: Vec<ReplayEvent> = Vec::new();
+            for event in frame.get("events").and_then(|events| events.as_array()).unwrap_or(&Vec::new()) {
+                if let Some(event) = parse_event(event)? {
+                    events.push(event);
+                }
+            }
+
+            let mut deposited: Vec<usize> = players.iter().map(|_| 0).collect();
+            if let Some(frame_deposited) = frame.get("deposited").and_then(|deposited| deposited.as_object()) {
+                for (player_key, amount) in frame_deposited {
+                    let player_id = parse_id(player_key)?;
+                    let player_deposited = deposited.get_mut(player_id).ok_or_else(|| format!("turn {} has deposits of unknown player {}", turn_number, player_id))?;
+                    *player_deposited = amount.as_u64().unwrap_or(0) as usize;
+                }
+            }
+
+            frames.push(ReplayFrame {
+                turn_number,
+                players: players.clone(),
+                ships,
+                dropoffs: dropoffs.clone(),
+                cell_updates,
+                moves,
+                events,
+                deposited,
+            });
+
+            // What this turn changed shows up in the next frame
+            cell_updates = Vec::new();
+            for cell in frame.get("cells").and_then(|cells| cells.as_array()).unwrap_or(&Vec::new()) {
+                cell_updates.push((get_position(cell)?, get_usize(cell, "production")?));
+            }
+            if let Some(energy) = frame.get("energy").and_then(|energy| energy.as_object()) {
+                for (player_key, amount) in energy {
+                    let player_id = parse_id(player_key)?;
+                    let player = players.get_mut(player_id).ok_or_else(|| format!("turn {} has energy of unknown player {}", turn_number, player_id))?;
+                    player.halite = amount.as_u64().unwrap_or(0) as usize;
+                }
+            }
+            for event in &frames.last().unwrap().events {
+                if let ReplayEvent::Construct { dropoff_id, owner, position } = event {
+                    dropoffs.insert(*dropoff_id, Dropoff { owner: *owner, id: *dropoff_id, position: *position });
+                }
+            }
+        }
+        Ok(frames)
+    }
+
+    pub fn get_turn_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    // The game as the given player saw it at the start of the given turn, counting from 1
+    pub fn get_game(&self, turn_number: usize, player_id: PlayerId) -> Game {
+        let mut map = self.initial_map.clone();
+        for frame in self.frames.iter().take(turn_number) {
+            for (position, halite) in &frame.cell_updates {
+                map.at_position_mut(position).halite = *halite;
+            }
+        }
+
+        let frame = &self.frames[turn_number - 1];
+        for player in &frame.players {
+            map.at_position_mut(&player.shipyard.position).structure = Structure::Shipyard(player.id);
+        }
+        for dropoff in frame.dropoffs.values() {
+            map.at_position_mut(&dropoff.position).structure = Structure::Dropoff(dropoff.id);
+        }
+
+        let mut game = Game::from_state(self.constants.clone(), player_id, frame.players.clone(), map);
+        game.turn_number = turn_number;
+        game.ships = frame.ships.clone();
+        game.dropoffs = frame.dropoffs.clone();
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hlt::PlayerId;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use super::Replay;
+
+    // Four turns on an 8x8 map: player 0 spawns a ship, moves it east onto a 100 halite cell and mines there
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/replays/fixture-8x8.hlt");
+    // The same game laid out the way the engine writes it: compact, keys in order, zstd compressed
+    const ENGINE_LAYOUT_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/replays/engine-layout-8x8.hlt");
+
+    #[test]
+    fn reads_the_fixture_turn_by_turn() {
+        let replay = Replay::read(FIXTURE).unwrap();
+        // The first full frame is the starting state, not a turn
+        assert_eq!(replay.get_turn_count(), 4);
+        assert_eq!(replay.player_names, vec!["Fixture0".to_string(), "Fixture1".to_string()]);
+        assert_eq!(replay.ranks, vec![Some(1), Some(2)]);
+
+        let first = replay.get_game(1, PlayerId(0));
+        assert!(first.ships.is_empty());
+        assert_eq!(first.players[0].halite, 5000);
+        assert_eq!(first.map.at_position(&Position { x: 2, y: 1 }).halite, 100);
+        assert_eq!(first.map.at_position(&Position { x: 3, y: 4 }).halite, 70);
+
+        // The spawn and its cost show up in the frame after the turn they happened on
+        let second = replay.get_game(2, PlayerId(0));
+        assert_eq!(second.players[0].halite, 4000);
+        assert!(second.players[0].ship_ids == vec![ShipId(0)]);
+        assert!(second.ships[&ShipId(0)].position.equal(&Position { x: 1, y: 1 }));
+
+        // The cell mined on turn 3 only changes for the bots on turn 4
+        let third = replay.get_game(3, PlayerId(0));
+        assert!(third.ships[&ShipId(0)].position.equal(&Position { x: 2, y: 1 }));
+        assert_eq!(third.ships[&ShipId(0)].halite, 0);
+        assert_eq!(third.map.at_position(&Position { x: 2, y: 1 }).halite, 100);
+
+        let fourth = replay.get_game(4, PlayerId(1));
+        assert!(fourth.my_id == PlayerId(1));
+        assert_eq!(fourth.turn_number, 4);
+        assert_eq!(fourth.ships[&ShipId(0)].halite, 25);
+        assert_eq!(fourth.map.at_position(&Position { x: 2, y: 1 }).halite, 75);
+    }
+
+    #[test]
+    fn rejects_ships_of_unknown_players() {
+        let text = std::fs::read_to_string(FIXTURE).unwrap().replace("\"entities\": {\"0\": {\"0\"", "\"entities\": {\"5\": {\"0\"");
+        assert!(Replay::parse(&text).is_err());
+    }
+
+    #[test]
+    fn reads_compressed_replays_in_the_engine_layout() {
+        assert!(std::fs::read(ENGINE_LAYOUT_FIXTURE).unwrap()[0] != b'{');
+        let compressed = Replay::read(ENGINE_LAYOUT_FIXTURE).unwrap();
+        let plain = Replay::read(FIXTURE).unwrap();
+        assert_eq!(compressed.get_turn_count(), plain.get_turn_count());
+        assert_eq!(compressed.player_names, plain.player_names);
+        assert_eq!(compressed.ranks, plain.ranks);
+        assert_eq!(compressed.map_seed, 7);
+        assert_eq!(compressed.constants.max_turns, 4);
+
+        for turn_number in 1..plain.get_turn_count() + 1 {
+            let expected = plain.get_game(turn_number, PlayerId(0));
+            let actual = compressed.get_game(turn_number, PlayerId(0));
+            assert_eq!(actual.players[0].halite, expected.players[0].halite);
+            assert!(actual.players[0].ship_ids == expected.players[0].ship_ids);
+            for (ship_id, ship) in &expected.ships {
+                assert!(actual.ships[ship_id].position.equal(&ship.position));
+                assert_eq!(actual.ships[ship_id].halite, ship.halite);
+            }
+            for (expected_row, actual_row) in expected.map.cells.iter().zip(&actual.map.cells) {
+                for (expected_cell, actual_cell) in expected_row.iter().zip(actual_row) {
+                    assert_eq!(actual_cell.halite, expected_cell.halite);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_replays_that_do_not_decompress() {
+        let mut bytes = std::fs::read(ENGINE_LAYOUT_FIXTURE).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        let path = std::env::temp_dir().join(format!("truncated-{}.hlt", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let result = Replay::read(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.err().unwrap().contains("can't decompress"));
+    }
+}
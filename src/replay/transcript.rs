@@ -0,0 +1,144 @@
+use engine::commands::EngineCommand;
+use engine::runner::BotProcess;
+use hlt::command::Command;
+use hlt::PlayerId;
+use replay::reader::Replay;
+use replay::reader::ReplayFrame;
+use std::time::Duration;
+
+// What Game::new reads: the constants, player count and id, shipyards, and the full map
+pub fn get_init_lines(replay: &Replay, player_id: PlayerId) -> Vec<String> {
+    let mut lines = vec![replay.constants_line.clone(), format!("{} {}", replay.initial_players.len(), player_id.0)];
+    for player in &replay.initial_players {
+        lines.push(format!("{} {} {}", player.id.0, player.shipyard.position.x, player.shipyard.position.y));
+    }
+    lines.push(format!("{} {}", replay.initial_map.width, replay.initial_map.height));
+    for row in &replay.initial_map.cells {
+        let halite: Vec<String> = row.iter().map(|cell| cell.halite.to_string()).collect();
+        lines.push(halite.join(" "));
+    }
+    lines
+}
+
+// What Game::update_frame reads at the start of a turn
+pub fn get_frame_lines(frame: &ReplayFrame) -> Vec<String> {
+    let mut lines = vec![frame.turn_number.to_string()];
+    for player in &frame.players {
+        lines.push(format!("{} {} {} {}", player.id.0, player.ship_ids.len(), player.dropoff_ids.len(), player.halite));
+        for ship_id in &player.ship_ids {
+            let ship = &frame.ships[ship_id];
+            lines.push(format!("{} {} {} {}", ship.id.0, ship.position.x, ship.position.y, ship.halite));
+        }
+        for dropoff_id in &player.dropoff_ids {
+            let dropoff = &frame.dropoffs[dropoff_id];
+            lines.push(format!("{} {} {}", dropoff.id.0, dropoff.position.x, dropoff.position.y));
+        }
+    }
+    lines.push(frame.cell_updates.len().to_string());
+    for (position, halite) in &frame.cell_updates {
+        lines.push(format!("{} {} {}", position.x, position.y, halite));
+    }
+    lines
+}
+
+// The frames the server sent the player: every turn up to the last one where it still had
+// ships or answered with commands
+pub fn get_player_frames(replay: &Replay, player_id: PlayerId) -> &[ReplayFrame] {
+    let last_turn = replay.frames.iter()
+        .rposition(|frame| frame.moves.contains_key(&player_id) || !frame.players[player_id.0].ship_ids.is_empty())
+        .map_or(0, |index| index + 1);
+    &replay.frames[..last_turn]
+}
+
+// The whole input stream the player got during the game, one line per line
+pub fn get_transcript(replay: &Replay, player_id: PlayerId) -> Vec<String> {
+    let mut lines = get_init_lines(replay, player_id);
+    for frame in get_player_frames(replay, player_id) {
+        lines.extend(get_frame_lines(frame));
+    }
+    lines
+}
+
+// Staying still is the same as sending nothing, and the order of the commands doesn't matter
+fn normalize(commands: &[Command]) -> Vec<String> {
+    let mut commands: Vec<String> = commands.iter()
+        .map(|command| command.0.clone())
+        .filter(|command| !command.ends_with(" o"))
+        .collect();
+    commands.sort();
+    commands
+}
+
+pub struct TurnComparison {
+    pub turn_number: usize,
+    pub logged: Vec<String>,
+    pub rerun: Vec<String>,
+}
+
+impl TurnComparison {
+    pub fn is_same(&self) -> bool {
+        self.logged == self.rerun
+    }
+}
+
+// Plays the transcript of a logged game to a bot and lines up its commands with the logged ones.
+// The bot always gets the logged frames, so its commands only show what it would have done there.
+pub fn rerun(replay: &Replay, player_id: PlayerId, command: &str, init_timeout: Duration, turn_timeout: Duration) -> Result<Vec<TurnComparison>, String> {
+    let mut bot = BotProcess::launch(command)?;
+    bot.send(&get_init_lines(replay, player_id)).and_then(|_| bot.receive(init_timeout))
+        .map_err(|reason| format!("bot failed to start: {}", reason))?;
+
+    let mut comparisons: Vec<TurnComparison> = Vec::new();
+    for frame in get_player_frames(replay, player_id) {
+        let line = bot.send(&get_frame_lines(frame)).and_then(|_| bot.receive(turn_timeout))
+            .map_err(|reason| format!("bot failed on turn {}: {}", frame.turn_number, reason))?;
+        let commands: Vec<Command> = EngineCommand::parse_line(&line)
+            .map_err(|reason| format!("bot sent bad commands on turn {}: {}", frame.turn_number, reason))?
+            .iter()
+            .map(|command| command.to_command())
+            .collect();
+        comparisons.push(TurnComparison {
+            turn_number: frame.turn_number,
+            logged: normalize(frame.moves.get(&player_id).map_or(&[], |moves| moves.as_slice())),
+            rerun: normalize(&commands),
+        });
+    }
+    Ok(comparisons)
+}
+
+#[cfg(test)]
+mod tests {
+    use hlt::PlayerId;
+    use replay::reader::Replay;
+    use std::fs;
+    use super::get_transcript;
+    use zstd;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/replays/fixture-8x8.hlt");
+    const ENGINE_LAYOUT_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/replays/engine-layout-8x8.hlt");
+
+    #[test]
+    fn transcript_starts_the_way_the_server_did() {
+        let replay = Replay::read(ENGINE_LAYOUT_FIXTURE).unwrap();
+        let transcript = get_transcript(&replay, PlayerId(1));
+        let file = String::from_utf8(zstd::decode_all(fs::read(ENGINE_LAYOUT_FIXTURE).unwrap().as_slice()).unwrap()).unwrap();
+
+        // The constants go out exactly as the engine wrote them down
+        assert!(transcript[0].starts_with("{\"CAPTURE_ENABLED\":false,\"CAPTURE_RADIUS\":3,"));
+        assert!(transcript[0].ends_with("\"game_seed\":7}"));
+        assert!(file.contains(&format!("\"GAME_CONSTANTS\":{},", transcript[0])));
+        assert_eq!(&transcript[1..6], &["2 1", "0 1 1", "1 6 6", "8 8", "0 10 20 30 40 50 60 70"]);
+        assert_eq!(transcript[12], "70 80 90 100 110 120 130 140");
+        assert_eq!(&transcript[13..17], &["1", "0 0 0 5000", "1 0 0 5000", "0"]);
+        assert_eq!(&transcript[17..21], &["2", "0 1 0 4000", "0 1 1 0", "1 0 0 5000"]);
+    }
+
+    #[test]
+    fn constants_keep_their_spacing_and_order() {
+        let replay = Replay::read(FIXTURE).unwrap();
+        let file = fs::read_to_string(FIXTURE).unwrap();
+        let transcript = get_transcript(&replay, PlayerId(0));
+        assert!(transcript[0].starts_with("{\"CAPTURE_ENABLED\": false, \"DROPOFF_COST\": 4000,"));
+        assert!(file.contains(&format!("\"GAME_CONSTANTS\": {},", transcript[0])));
+    }
+}
@@ -0,0 +1,449 @@
+use engine::commands::EngineCommand;
+use engine::simulator::Simulator;
+use engine::simulator::TurnEvents;
+use hlt::game::Game;
+use hlt::map_cell::Structure;
+use hlt::PlayerId;
+use hlt::position::Position;
+use serde_json;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use zstd;
+
+const REPLAY_FILE_VERSION: usize = 3;
+const MAP_GENERATOR: &str = "Fractal Value Noise Tile";
+const COMPRESSION_LEVEL: i32 = 3;
+
+// Running totals behind the statistics the viewer shows for each player
+struct PlayerStatistics {
+    ships_spawned: usize,
+    ships_peak: usize,
+    last_turn_ship_spawn: usize,
+    total_mined: usize,
+    total_bonus: usize,
+    total_dropped: usize,
+    // Halite brought to each of the player's structures
+    dropped_at: Vec<(Position, usize)>,
+    all_collisions: usize,
+    self_collisions: usize,
+    dropoff_collisions: usize,
+    // How far ships got from the shipyard, summed over every ship on every turn
+    entity_distance: usize,
+    entity_turns: usize,
+    max_entity_distance: usize,
+    // Ships within inspiration range of an enemy ship, on every turn
+    interaction_opportunities: usize,
+}
+
+impl PlayerStatistics {
+    fn new() -> PlayerStatistics {
+        PlayerStatistics {
+            ships_spawned: 0,
+            ships_peak: 0,
+            last_turn_ship_spawn: 0,
+            total_mined: 0,
+            total_bonus: 0,
+            total_dropped: 0,
+            dropped_at: Vec::new(),
+            all_collisions: 0,
+            self_collisions: 0,
+            dropoff_collisions: 0,
+            entity_distance: 0,
+            entity_turns: 0,
+            max_entity_distance: 0,
+            interaction_opportunities: 0,
+        }
+    }
+}
+
+fn to_position(position: &Position) -> Value {
+    json!({ "x": position.x, "y": position.y })
+}
+
+fn to_move(command: &EngineCommand) -> Value {
+    match command {
+        EngineCommand::Spawn => json!({ "type": "g" }),
+        EngineCommand::Construct(ship_id) => json!({ "type": "c", "id": ship_id.0 }),
+        EngineCommand::Move(ship_id, direction) => json!({ "type": "m", "id": ship_id.0, "direction": direction.get_char_encoding().to_string() }),
+    }
+}
+
+// Player ids are object keys in replays
+fn by_player(game: &Game, value: impl Fn(PlayerId) -> Value) -> Value {
+    let mut object = Map::new();
+    for player in &game.players {
+        object.insert(player.id.0.to_string(), value(player.id));
+    }
+    Value::Object(object)
+}
+
+// Records a simulated game in the format the server writes to replays/, so the Halite III viewer
+// can play it back. Every turn is one of the full frames, laid out the way Replay reads them.
+pub struct ReplayWriter {
+    constants: Value,
+    map_seed: u64,
+    players: Vec<(PlayerId, Position, usize)>,
+    production_map: Value,
+    frames: Vec<Value>,
+    statistics: Vec<PlayerStatistics>,
+}
+
+impl ReplayWriter {
+    // Starts from the game as it is before the first turn
+    pub fn new(constants_line: &str, map_seed: u64, game: &Game) -> ReplayWriter {
+        let constants = serde_json::from_str(constants_line).unwrap_or(Value::Null);
+        let players = game.players.iter().map(|player| (player.id, player.shipyard.position, player.halite)).collect();
+        let grid: Vec<Value> = game.map.cells.iter()
+            .map(|row| Value::Array(row.iter().map(|cell| json!({ "energy": cell.halite })).collect()))
+            .collect();
+        let production_map = json!({ "width": game.map.width, "height": game.map.height, "grid": grid, "map_generator": MAP_GENERATOR });
+        let first_frame = json!({
+            "entities": {},
+            "moves": {},
+            "events": [],
+            "cells": [],
+            "energy": by_player(game, |player_id| json!(game.players[player_id.0].halite)),
+            "deposited": by_player(game, |_| json!(0)),
+        });
+        ReplayWriter {
+            constants,
+            map_seed,
+            players,
+            production_map,
+            frames: vec![first_frame],
+            statistics: game.players.iter().map(|_| PlayerStatistics::new()).collect(),
+        }
+    }
+
+    // Adds the turn the simulator just processed, given the game as it was when the turn started
+    pub fn record_turn(&mut self, before: &Game, commands: &HashMap<PlayerId, Vec<EngineCommand>>, events: &TurnEvents, simulator: &Simulator) {
+        let after = &simulator.game;
+
+        let mut entities = Map::new();
+        let inspiration_radius = before.constants.inspiration_radius;
+        for player in &before.players {
+            let mut ships = Map::new();
+            for ship_id in &player.ship_ids {
+                let ship = &before.ships[ship_id];
+                let statistics = &mut self.statistics[player.id.0];
+                let distance = before.map.calculate_distance(&ship.position, &player.shipyard.position);
+                statistics.entity_distance += distance;
+                statistics.entity_turns += 1;
+                statistics.max_entity_distance = statistics.max_entity_distance.max(distance);
+                if before.ships.values().any(|other| other.owner != player.id && before.map.calculate_distance(&other.position, &ship.position) <= inspiration_radius) {
+                    statistics.interaction_opportunities += 1;
+                }
+                ships.insert(ship_id.0.to_string(), json!({
+                    "x": ship.position.x,
+                    "y": ship.position.y,
+                    "energy": ship.halite,
+                    "is_inspired": simulator.inspired.contains(ship_id),
+                }));
+            }
+            entities.insert(player.id.0.to_string(), Value::Object(ships));
+            let statistics = &mut self.statistics[player.id.0];
+            statistics.ships_peak = statistics.ships_peak.max(player.ship_ids.len());
+        }
+
+        let mut moves = Map::new();
+        let mut player_ids: Vec<&PlayerId> = commands.keys().collect();
+        player_ids.sort_by_key(|player_id| player_id.0);
+        for player_id in player_ids {
+            moves.insert(player_id.0.to_string(), Value::Array(commands[player_id].iter().map(to_move).collect()));
+        }
+
+        let mut frame_events: Vec<Value> = Vec::new();
+        for ship in &events.spawns {
+            frame_events.push(json!({ "type": "spawn", "id": ship.id.0, "owner_id": ship.owner.0, "location": to_position(&ship.position), "energy": 0 }));
+            let statistics = &mut self.statistics[ship.owner.0];
+            statistics.ships_spawned += 1;
+            statistics.last_turn_ship_spawn = before.turn_number;
+        }
+        for dropoff_id in &events.constructions {
+            let dropoff = &after.dropoffs[dropoff_id];
+            frame_events.push(json!({ "type": "construct", "id": dropoff_id.0, "owner_id": dropoff.owner.0, "location": to_position(&dropoff.position) }));
+        }
+        for collision in &events.collisions {
+            let ship_ids: Vec<usize> = collision.ship_ids.iter().map(|ship_id| ship_id.0).collect();
+            frame_events.push(json!({ "type": "shipwreck", "ships": ship_ids, "location": to_position(&collision.position) }));
+
+            // Ships spawned this turn aren't in the frame the turn started from
+            let owners: Vec<PlayerId> = collision.ship_ids.iter()
+                .filter_map(|ship_id| before.ships.get(ship_id).or_else(|| events.spawns.iter().find(|ship| ship.id == *ship_id)))
+                .map(|ship| ship.owner)
+                .collect();
+            let is_on_structure = after.map.at_position(&collision.position).structure.is_some();
+            for owner in &owners {
+                let statistics = &mut self.statistics[owner.0];
+                statistics.all_collisions += 1;
+                if owners.iter().filter(|other| *other == owner).count() > 1 {
+                    statistics.self_collisions += 1;
+                }
+                if is_on_structure {
+                    statistics.dropoff_collisions += 1;
+                }
+            }
+        }
+
+        let mut cells: Vec<Value> = Vec::new();
+        for row in &after.map.cells {
+            for cell in row {
+                if cell.halite != before.map.at_position(&cell.position).halite {
+                    cells.push(json!({ "x": cell.position.x, "y": cell.position.y, "production": cell.halite }));
+                }
+            }
+        }
+
+        for (player_id, halite) in &events.mined {
+            self.statistics[player_id.0].total_mined += halite;
+        }
+        for (player_id, halite) in &events.deposited {
+            self.statistics[player_id.0].total_dropped += halite;
+        }
+        for (player_id, halite) in &events.bonus {
+            self.statistics[player_id.0].total_bonus += halite;
+        }
+        for (position, halite) in &events.deposits {
+            let owner = match after.map.at_position(position).structure {
+                Structure::Shipyard(player_id) => player_id,
+                Structure::Dropoff(dropoff_id) => after.dropoffs[&dropoff_id].owner,
+                Structure::None => continue,
+            };
+            let dropped_at = &mut self.statistics[owner.0].dropped_at;
+            match dropped_at.iter_mut().find(|(structure, _)| structure.equal(position)) {
+                Some((_, total)) => *total += halite,
+                None => dropped_at.push((*position, *halite)),
+            }
+        }
+        let statistics = &self.statistics;
+        self.frames.push(json!({
+            "entities": entities,
+            "moves": moves,
+            "events": frame_events,
+            "cells": cells,
+            "energy": by_player(after, |player_id| json!(after.players[player_id.0].halite)),
+            "deposited": by_player(after, |player_id| json!(statistics[player_id.0].total_dropped)),
+        }));
+    }
+
+    fn get_player_statistics(&self, simulator: &Simulator) -> Vec<Value> {
+        let game = &simulator.game;
+        let rankings = simulator.get_rankings();
+        game.players.iter()
+            .map(|player| {
+                let statistics = &self.statistics[player.id.0];
+                let last_turn_alive = match &simulator.eliminations[player.id.0] {
+                    Some(elimination) => elimination.turn_number,
+                    None => self.frames.len() - 1,
+                };
+                let carried_at_end: usize = player.ship_ids.iter().map(|ship_id| game.ships[ship_id].halite).sum();
+                let mining_efficiency = if statistics.total_mined > 0 {
+                    statistics.total_dropped as f64 / statistics.total_mined as f64
+                } else {
+                    0.0
+                };
+                let average_entity_distance = statistics.entity_distance.checked_div(statistics.entity_turns).unwrap_or(0);
+                let mut structures = vec![player.shipyard.position];
+                structures.extend(player.dropoff_ids.iter().map(|dropoff_id| game.dropoffs[dropoff_id].position));
+                let halite_per_dropoff: Vec<Value> = structures.iter()
+                    .map(|position| {
+                        let halite = statistics.dropped_at.iter().find(|(structure, _)| structure.equal(position)).map_or(0, |(_, halite)| *halite);
+                        json!({ "position": to_position(position), "halite": halite })
+                    })
+                    .collect();
+                json!({
+                    "player_id": player.id.0,
+                    "random_id": 0,
+                    "rank": rankings.iter().position(|player_id| *player_id == player.id).unwrap() + 1,
+                    "last_turn_alive": last_turn_alive,
+                    "last_turn_ship_spawn": statistics.last_turn_ship_spawn,
+                    "ships_spawned": statistics.ships_spawned,
+                    "ships_peak": statistics.ships_peak,
+                    "total_production": statistics.total_mined,
+                    "total_mined": statistics.total_mined,
+                    "total_bonus": statistics.total_bonus,
+                    "total_dropped": statistics.total_dropped,
+                    "halite_per_dropoff": halite_per_dropoff,
+                    "carried_at_end": carried_at_end,
+                    "mining_efficiency": mining_efficiency,
+                    "number_dropoffs": player.dropoff_ids.len(),
+                    "all_collisions": statistics.all_collisions,
+                    "self_collisions": statistics.self_collisions,
+                    "dropoff_collisions": statistics.dropoff_collisions,
+                    "average_entity_distance": average_entity_distance,
+                    "max_entity_distance": statistics.max_entity_distance,
+                    "interaction_opportunities": statistics.interaction_opportunities,
+                    // Capturing ships is off in every game the server runs
+                    "ships_captured": 0,
+                    "ships_given": 0,
+                    "total_mined_from_captured": 0,
+                    "final_production": player.halite,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self, simulator: &Simulator, names: &[String]) -> Value {
+        let players: Vec<Value> = self.players.iter()
+            .map(|(player_id, shipyard, halite)| json!({
+                "player_id": player_id.0,
+                "name": names.get(player_id.0).cloned().unwrap_or_default(),
+                "factory_location": to_position(shipyard),
+                "energy": halite,
+                "entities": [],
+            }))
+            .collect();
+        json!({
+            "ENGINE_VERSION": format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            "REPLAY_FILE_VERSION": REPLAY_FILE_VERSION,
+            "GAME_CONSTANTS": self.constants,
+            "map_generator_seed": self.map_seed,
+            "number_of_players": players.len(),
+            "players": players,
+            "production_map": self.production_map,
+            "full_frames": self.frames,
+            "game_statistics": {
+                "number_turns": self.frames.len() - 1,
+                "player_statistics": self.get_player_statistics(simulator),
+            },
+        })
+    }
+
+    // Writes the replay zstd compressed like the server does
+    pub fn write(&self, path: &str, simulator: &Simulator, names: &[String]) -> Result<(), String> {
+        let json = serde_json::to_vec(&self.to_json(simulator, names)).map_err(|error| format!("can't encode the replay: {}", error))?;
+        let compressed = zstd::encode_all(json.as_slice(), COMPRESSION_LEVEL).map_err(|error| format!("can't compress the replay: {}", error))?;
+        fs::write(path, compressed).map_err(|error| format!("can't write {}: {}", path, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::runner::Match;
+    use engine::runner::MatchSettings;
+    use hlt::game::Game;
+    use hlt::PlayerId;
+    use replay::reader::Replay;
+    use serde_json;
+    use serde_json::Value;
+    use std::env;
+    use std::fs;
+    use zstd;
+
+    // A short game laid out the way the engine writes replays, see replays/README.md
+    const ENGINE_LAYOUT_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/replays/engine-layout-8x8.hlt");
+
+    fn read_json(path: &str) -> Value {
+        let json = zstd::decode_all(fs::read(path).unwrap().as_slice()).unwrap();
+        serde_json::from_slice(&json).unwrap()
+    }
+
+    fn get_keys(value: &Value) -> Vec<String> {
+        value.as_object().unwrap().keys().cloned().collect()
+    }
+
+    fn find<'a>(frames: &'a Value, list: &str, predicate: impl Fn(&Value) -> bool) -> &'a Value {
+        frames.as_array().unwrap().iter()
+            .flat_map(|frame| match &frame[list] {
+                Value::Array(values) => values.iter().collect::<Vec<&Value>>(),
+                Value::Object(by_player) => by_player.values().flat_map(|values| values.as_array().unwrap()).collect(),
+                _ => Vec::new(),
+            })
+            .find(|value| predicate(value))
+            .unwrap()
+    }
+
+    fn assert_same_state(turn_number: usize, expected: &Game, actual: &Game) {
+        assert_eq!(actual.turn_number, turn_number);
+        for (expected_player, actual_player) in expected.players.iter().zip(&actual.players) {
+            assert_eq!(actual_player.halite, expected_player.halite, "player {} halite on turn {}", expected_player.id.0, turn_number);
+            assert!(actual_player.ship_ids == expected_player.ship_ids, "player {} ships on turn {}", expected_player.id.0, turn_number);
+            assert!(actual_player.dropoff_ids == expected_player.dropoff_ids, "player {} dropoffs on turn {}", expected_player.id.0, turn_number);
+        }
+        for (ship_id, ship) in &expected.ships {
+            let actual_ship = &actual.ships[ship_id];
+            assert!(actual_ship.owner == ship.owner && actual_ship.position.equal(&ship.position) && actual_ship.halite == ship.halite,
+                "ship {} on turn {}", ship_id.0, turn_number);
+        }
+        for (expected_row, actual_row) in expected.map.cells.iter().zip(&actual.map.cells) {
+            for (expected_cell, actual_cell) in expected_row.iter().zip(actual_row) {
+                assert!(actual_cell.halite == expected_cell.halite && actual_cell.structure == expected_cell.structure,
+                    "cell {}, {} on turn {}", expected_cell.position.x, expected_cell.position.y, turn_number);
+            }
+        }
+    }
+
+    #[test]
+    fn written_replays_read_back_as_the_simulated_game() {
+        let mut settings = MatchSettings::new(5, 32);
+        settings.max_turns = Some(60);
+        settings.record_replay = true;
+        let commands: Vec<String> = ["builtin:miner", "builtin:rammer", "builtin:starter", "builtin:random"].iter().map(|command| command.to_string()).collect();
+        let mut game = Match::new(settings, &commands).unwrap();
+        game.init();
+        let mut states: Vec<Game> = Vec::new();
+        while !game.simulator.is_over() {
+            states.push(game.simulator.game.clone());
+            game.play_turn();
+        }
+
+        let path = env::temp_dir().join(format!("writer-round-trip-{}.hlt", std::process::id()));
+        let path = path.to_str().unwrap();
+        game.write_replay(path).unwrap();
+        let replay = Replay::read(path);
+        fs::remove_file(path).unwrap();
+        let replay = replay.unwrap();
+
+        assert_eq!(replay.get_turn_count(), states.len());
+        for (turn, state) in states.iter().enumerate() {
+            assert_same_state(turn + 1, state, &replay.get_game(turn + 1, PlayerId(0)));
+        }
+    }
+
+    #[test]
+    fn written_replays_use_the_engine_layout() {
+        let mut settings = MatchSettings::new(3, 32);
+        settings.max_turns = Some(60);
+        settings.record_replay = true;
+        let commands = vec!["builtin:starter".to_string(), "builtin:starter".to_string()];
+        let mut game = Match::new(settings, &commands).unwrap();
+        game.run();
+
+        let path = env::temp_dir().join(format!("writer-layout-{}.hlt", std::process::id()));
+        let path = path.to_str().unwrap();
+        game.write_replay(path).unwrap();
+        let written = read_json(path);
+        fs::remove_file(path).unwrap();
+        let engine = read_json(ENGINE_LAYOUT_FIXTURE);
+
+        assert_eq!(get_keys(&written), get_keys(&engine));
+        for key in &["GAME_CONSTANTS", "production_map", "game_statistics"] {
+            assert_eq!(get_keys(&written[key]), get_keys(&engine[key]), "{}", key);
+        }
+        assert_eq!(get_keys(&written["players"][0]), get_keys(&engine["players"][0]));
+        assert_eq!(get_keys(&written["production_map"]["grid"][0][0]), get_keys(&engine["production_map"]["grid"][0][0]));
+        assert_eq!(get_keys(&written["game_statistics"]["player_statistics"][0]), get_keys(&engine["game_statistics"]["player_statistics"][0]));
+
+        let (written_frames, engine_frames) = (&written["full_frames"], &engine["full_frames"]);
+        assert_eq!(get_keys(&written_frames[1]), get_keys(&engine_frames[1]));
+        assert_eq!(get_keys(&written_frames[5]["entities"]["0"]["0"]), get_keys(&engine_frames[2]["entities"]["0"]["0"]));
+        assert_eq!(get_keys(&written_frames[5]["cells"][0]), get_keys(&engine_frames[3]["cells"][0]));
+        let is_spawn = |event: &Value| event["type"] == "spawn";
+        assert_eq!(get_keys(find(written_frames, "events", is_spawn)), get_keys(find(engine_frames, "events", is_spawn)));
+        for kind in &["g", "m"] {
+            let is_kind = |command: &Value| command["type"] == *kind;
+            assert_eq!(get_keys(find(written_frames, "moves", is_kind)), get_keys(find(engine_frames, "moves", is_kind)), "{} move", kind);
+        }
+
+        for statistics in written["game_statistics"]["player_statistics"].as_array().unwrap() {
+            let per_dropoff: u64 = statistics["halite_per_dropoff"].as_array().unwrap().iter().map(|dropoff| dropoff["halite"].as_u64().unwrap()).sum();
+            assert_eq!(per_dropoff, statistics["total_dropped"].as_u64().unwrap());
+            assert!(per_dropoff > 0);
+        }
+
+        // Every two player game starts from the same frame, down to the byte
+        assert_eq!(serde_json::to_string(&written_frames[0]).unwrap(), serde_json::to_string(&engine_frames[0]).unwrap());
+    }
+}
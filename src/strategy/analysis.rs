@@ -0,0 +1,58 @@
+use hlt::game::Game;
+use hlt::position::Position;
+
+// Distance from every cell to our closest shipyard or dropoff, refreshed every turn
+pub struct BaseDistances {
+    pub width: usize,
+    pub height: usize,
+    pub distances: Vec<Vec<usize>>,
+    pub nearest: Vec<Vec<Position>>,
+}
+
+impl BaseDistances {
+    pub fn new(width: usize, height: usize) -> BaseDistances {
+        BaseDistances {
+            width,
+            height,
+            distances: vec![vec![0; width]; height],
+            nearest: vec![vec![Position { x: 0, y: 0 }; width]; height],
+        }
+    }
+
+    pub fn update_frame(&mut self, game: &Game) {
+        let me = &game.players[game.my_id.0];
+        let mut bases: Vec<Position> = vec![me.shipyard.position];
+        for dropoff_id in &me.dropoff_ids {
+            bases.push(game.dropoffs[dropoff_id].position);
+        }
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let position = Position { x: x as i32, y: y as i32 };
+                let nearest = *bases.iter()
+                    .min_by_key(|base| game.map.calculate_distance(&position, base))
+                    .unwrap();
+                self.distances[y][x] = game.map.calculate_distance(&position, &nearest);
+                self.nearest[y][x] = game.map.normalize(&nearest);
+            }
+        }
+    }
+
+    pub fn get_distance(&self, position: &Position) -> usize {
+        let position = self.normalize(position);
+        self.distances[position.y as usize][position.x as usize]
+    }
+
+    pub fn get_nearest(&self, position: &Position) -> Position {
+        let position = self.normalize(position);
+        self.nearest[position.y as usize][position.x as usize]
+    }
+
+    pub fn normalize(&self, position: &Position) -> Position {
+        let width = self.width as i32;
+        let height = self.height as i32;
+        let x = ((position.x % width) + width) % width;
+        let y = ((position.y % height) + height) % height;
+        Position { x, y }
+    }
+}
@@ -0,0 +1,199 @@
+use hlt::log::Log;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    TwoPlayer,
+    FourPlayer,
+}
+
+impl Mode {
+    pub fn from_player_count(num_players: usize) -> Mode {
+        if num_players <= 2 { Mode::TwoPlayer } else { Mode::FourPlayer }
+    }
+
+    pub fn get_prefix(&self) -> &str {
+        match self {
+            Mode::TwoPlayer => "2p",
+            Mode::FourPlayer => "4p",
+        }
+    }
+}
+
+// Tunables for the strategies. Defaults depend on the number of players and can be overridden
+// from a config file with one "key = value" per line and from "key=value" command line arguments,
+// in that order. Keys prefixed with "2p." or "4p." only apply to games with that many players.
+pub struct Config {
+    pub mode: Mode,
+    pub min_cell_halite: usize,
+    pub near_return_halite: usize,
+    pub far_base_distance: usize,
+    pub spawn_payback_margin: f64,
+    pub return_home_margin: usize,
+    pub ram_min_enemy_halite: usize,
+    // Steer clear of every enemy ship, not only the ones of players known to ram
+    pub avoid_all_enemies: bool,
+    // Scales the value of mining targets next to enemy ships, positive to contest them, negative to keep away
+    pub enemy_contact_weight: f64,
+}
+
+impl Config {
+    pub fn new(mode: Mode) -> Config {
+        match mode {
+            // Trading ships with the only opponent is even, so fight for territory
+            Mode::TwoPlayer => Config {
+                mode,
+                min_cell_halite: 0,
+                near_return_halite: 600,
+                far_base_distance: 10,
+                spawn_payback_margin: 1.0,
+                return_home_margin: 5,
+                ram_min_enemy_halite: 300,
+                avoid_all_enemies: false,
+                enemy_contact_weight: 0.2,
+            },
+            // Every collision helps the two players not involved, so keep out of trouble
+            Mode::FourPlayer => Config {
+                mode,
+                min_cell_halite: 0,
+                near_return_halite: 600,
+                far_base_distance: 10,
+                spawn_payback_margin: 1.2,
+                return_home_margin: 5,
+                ram_min_enemy_halite: 700,
+                avoid_all_enemies: true,
+                enemy_contact_weight: -0.3,
+            },
+        }
+    }
+
+    // "config=<path>" loads a file first, every other "key=value" argument is applied after it
+    pub fn try_from_args(args: &[String], num_players: usize) -> Result<Config, String> {
+        let mut config = Config::new(Mode::from_player_count(num_players));
+        let settings: Vec<(String, String)> = args.iter()
+            .filter_map(|arg| Config::split_setting(arg))
+            .collect();
+
+        for (key, value) in &settings {
+            if key == "config" {
+                config.apply(&Config::read_file(value)?)?;
+            }
+        }
+
+        let overrides: HashMap<String, String> = settings.into_iter().filter(|(key, _)| key != "config").collect();
+        config.apply(&overrides)?;
+        Ok(config)
+    }
+
+    // For the bot itself, where a bad setting ends the game before it starts
+    pub fn from_args(args: &[String], num_players: usize) -> Config {
+        match Config::try_from_args(args, num_players) {
+            Ok(config) => config,
+            Err(error) => Log::panic(&format!("Error: config: {}.", error)),
+        }
+    }
+
+    pub fn log(&self) {
+        Log::log(&format!(
+            "Config ({}): min_cell_halite={} near_return_halite={} far_base_distance={} spawn_payback_margin={} return_home_margin={} ram_min_enemy_halite={} avoid_all_enemies={} enemy_contact_weight={}",
+            self.mode.get_prefix(), self.min_cell_halite, self.near_return_halite, self.far_base_distance, self.spawn_payback_margin,
+            self.return_home_margin, self.ram_min_enemy_halite, self.avoid_all_enemies, self.enemy_contact_weight));
+    }
+
+    // Plain keys go first so that the ones for our mode can override them
+    fn apply(&mut self, map: &HashMap<String, String>) -> Result<(), String> {
+        let prefix = format!("{}.", self.mode.get_prefix());
+        let mut general: HashMap<String, String> = HashMap::new();
+        let mut specific: HashMap<String, String> = HashMap::new();
+        let mut inactive: HashMap<String, String> = HashMap::new();
+        for (key, value) in map {
+            if key.starts_with(&prefix) {
+                specific.insert(key[prefix.len()..].to_string(), value.clone());
+            } else if key.starts_with("2p.") || key.starts_with("4p.") {
+                inactive.insert(key[prefix.len()..].to_string(), value.clone());
+            } else {
+                general.insert(key.clone(), value.clone());
+            }
+        }
+
+        // Settings for the other mode don't apply to this game, but a typo in them still gets caught
+        Config::new(self.mode).apply_settings(&inactive)?;
+        self.apply_settings(&general)?;
+        self.apply_settings(&specific)
+    }
+
+    fn apply_settings(&mut self, map: &HashMap<String, String>) -> Result<(), String> {
+        for (key, value) in map {
+            match key.as_str() {
+                "min_cell_halite" => self.min_cell_halite = Config::parse_value(key, value)?,
+                "near_return_halite" => self.near_return_halite = Config::parse_value(key, value)?,
+                "far_base_distance" => self.far_base_distance = Config::parse_value(key, value)?,
+                "spawn_payback_margin" => self.spawn_payback_margin = Config::parse_value(key, value)?,
+                "return_home_margin" => self.return_home_margin = Config::parse_value(key, value)?,
+                "ram_min_enemy_halite" => self.ram_min_enemy_halite = Config::parse_value(key, value)?,
+                "avoid_all_enemies" => self.avoid_all_enemies = Config::parse_value(key, value)?,
+                "enemy_contact_weight" => self.enemy_contact_weight = Config::parse_value(key, value)?,
+                _ => return Err(format!("unknown setting '{}'", key)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn split_setting(arg: &str) -> Option<(String, String)> {
+        let mut parts = arg.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => Some((key.trim().to_string(), value.trim().to_string())),
+            _ => None,
+        }
+    }
+
+    fn read_file(path: &str) -> Result<HashMap<String, String>, String> {
+        let mut contents = String::new();
+        if File::open(path).and_then(|mut file| file.read_to_string(&mut contents)).is_err() {
+            return Err(format!("couldn't read config file {}", path));
+        }
+
+        contents.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match Config::split_setting(line) {
+                Some(setting) => Ok(setting),
+                None => Err(format!("expected 'key = value' in {} but got '{}'", path, line)),
+            })
+            .collect()
+    }
+
+    fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+        value.parse::<T>().map_err(|_| format!("for {} got '{}' and failed to parse that", key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+    use super::Mode;
+
+    fn get_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn settings_for_our_mode_override_plain_ones() {
+        let args = get_args(&["overlord", "return_home_margin=8", "4p.return_home_margin=12", "2p.ram_min_enemy_halite=100"]);
+        let config = Config::try_from_args(&args, 4).unwrap();
+        assert!(config.mode == Mode::FourPlayer);
+        assert_eq!(config.return_home_margin, 12);
+        assert_eq!(config.ram_min_enemy_halite, 700);
+        assert_eq!(Config::try_from_args(&args, 2).unwrap().return_home_margin, 8);
+    }
+
+    #[test]
+    fn negative_margin_is_rejected() {
+        let error = Config::try_from_args(&get_args(&["return_home_margin=-3"]), 2).err().unwrap();
+        assert!(error.contains("return_home_margin"), "{}", error);
+        assert!(Config::try_from_args(&get_args(&["4p.return_home_margin=-3"]), 2).is_err());
+    }
+}
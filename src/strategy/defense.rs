@@ -0,0 +1,223 @@
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::navi::Navi;
+use hlt::PlayerId;
+use hlt::position::Position;
+use hlt::ShipId;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use strategy::tactics::Ram;
+
+// An enemy sitting still next to or on a base for this many turns is camping
+const PARKED_TURNS: usize = 2;
+// Players caught camping this many times get rammed on sight
+const REPEAT_OFFENSES: usize = 2;
+
+pub struct Intruder {
+    pub enemy_id: ShipId,
+    pub owner: PlayerId,
+    pub position: Position,
+    pub base: Position,
+    pub is_on_base: bool,
+    pub parked_turns: usize,
+}
+
+// Keeps an eye on enemy ships hanging around our shipyard and dropoffs, remembers which
+// players keep doing it, and decides who gets rammed out of the way.
+pub struct BaseDefense {
+    previous_positions: HashMap<ShipId, (Position, usize)>,
+    reported: HashSet<ShipId>,
+    pub offenses: HashMap<PlayerId, usize>,
+    pub intruders: Vec<Intruder>,
+}
+
+impl BaseDefense {
+    pub fn new() -> BaseDefense {
+        BaseDefense { previous_positions: HashMap::new(), reported: HashSet::new(), offenses: HashMap::new(), intruders: Vec::new() }
+    }
+
+    pub fn update_frame(&mut self, game: &Game) {
+        let me = &game.players[game.my_id.0];
+        let mut bases: Vec<Position> = vec![me.shipyard.position];
+        for dropoff_id in &me.dropoff_ids {
+            bases.push(game.dropoffs[dropoff_id].position);
+        }
+
+        // Ship ids never come back, so sunk campers can be forgotten
+        self.reported.retain(|ship_id| game.ships.contains_key(ship_id));
+
+        let mut positions: HashMap<ShipId, (Position, usize)> = HashMap::new();
+        self.intruders.clear();
+        for player in &game.players {
+            if player.id == game.my_id {
+                continue;
+            }
+            for ship_id in &player.ship_ids {
+                let enemy = &game.ships[ship_id];
+                let base = match bases.iter().find(|base| game.map.calculate_distance(base, &enemy.position) <= 1) {
+                    Some(base) => *base,
+                    None => continue,
+                };
+
+                let parked_turns = match self.previous_positions.get(ship_id) {
+                    Some((position, turns)) if position.equal(&enemy.position) => turns + 1,
+                    _ => 1,
+                };
+                positions.insert(*ship_id, (enemy.position, parked_turns));
+
+                if parked_turns >= PARKED_TURNS && !self.reported.contains(ship_id) {
+                    self.reported.insert(*ship_id);
+                    let offenses = self.offenses.entry(player.id).or_insert(0);
+                    *offenses += 1;
+                    Log::log(&format!("Player {} is camping our base at {}, {} with ship {} ({} offenses)", player.id.0, base.x, base.y, ship_id.0, offenses));
+                }
+
+                self.intruders.push(Intruder {
+                    enemy_id: *ship_id,
+                    owner: player.id,
+                    position: enemy.position,
+                    base,
+                    is_on_base: enemy.position.equal(&base),
+                    parked_turns,
+                });
+            }
+        }
+        self.intruders.sort_by_key(|intruder| intruder.enemy_id.0);
+        self.previous_positions = positions;
+    }
+
+    pub fn get_offenses(&self, player_id: &PlayerId) -> usize {
+        self.offenses.get(player_id).cloned().unwrap_or(0)
+    }
+
+    pub fn get_intruder_on(&self, base: &Position) -> Option<&Intruder> {
+        self.intruders.iter().find(|intruder| intruder.is_on_base && intruder.base.equal(base))
+    }
+
+    // Anything dropped on our own base is ours, so an enemy sitting on it gets rammed as soon as
+    // it looks like it is staying, using the adjacent ship with the most cargo since that cargo is
+    // deposited anyway. Enemies next to a base only get rammed when their owner keeps doing it,
+    // using the emptiest ship; otherwise our ships route around them or wait for them to leave.
+    pub fn plan(&self, game: &Game, navi: &Navi) -> Vec<Ram> {
+        let me = &game.players[game.my_id.0];
+        let mut used_ships: HashSet<ShipId> = HashSet::new();
+        let mut rams: Vec<Ram> = Vec::new();
+
+        for intruder in &self.intruders {
+            let enemy = &game.ships[&intruder.enemy_id];
+            let is_repeat_offender = self.get_offenses(&intruder.owner) >= REPEAT_OFFENSES;
+            let is_parked = intruder.parked_turns >= PARKED_TURNS;
+            let should_ram = if intruder.is_on_base {
+                is_parked || is_repeat_offender || enemy.halite > 0
+            } else {
+                is_parked && is_repeat_offender
+            };
+
+            if !should_ram {
+                Log::log(&format!("Enemy ship {} near base {}, {}: routing around and waiting", intruder.enemy_id.0, intruder.base.x, intruder.base.y));
+                continue;
+            }
+
+            let mut defenders: Vec<ShipId> = me.ship_ids.iter()
+                .filter(|ship_id| !used_ships.contains(ship_id))
+                .filter(|ship_id| game.map.calculate_distance(&game.ships[ship_id].position, &intruder.position) == 1)
+                .cloned()
+                .collect();
+            if intruder.is_on_base {
+                defenders.sort_by_key(|ship_id| (usize::MAX - game.ships[ship_id].halite, ship_id.0));
+            } else {
+                defenders.sort_by_key(|ship_id| (game.ships[ship_id].halite, ship_id.0));
+            }
+
+            match defenders.first() {
+                Some(ship_id) => {
+                    let ship = &game.ships[ship_id];
+                    let direction = navi.get_unsafe_moves(&ship.position, &intruder.position)[0];
+                    Log::log(&format!("Ship {} rams enemy ship {} camping at {}, {}", ship_id.0, intruder.enemy_id.0, intruder.position.x, intruder.position.y));
+                    used_ships.insert(*ship_id);
+                    rams.push(Ram { ship_id: *ship_id, enemy_id: intruder.enemy_id, direction, gain: enemy.halite as f64 });
+                },
+                None => Log::log(&format!("Enemy ship {} is camping at {}, {} but none of our ships is next to it", intruder.enemy_id.0, intruder.position.x, intruder.position.y)),
+            }
+        }
+        rams
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::direction::Direction;
+    use hlt::game::Game;
+    use hlt::navi::Navi;
+    use hlt::PlayerId;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use super::BaseDefense;
+
+    // Our shipyard at 4, 4 with an empty ship west of it and a laden one south of it
+    fn get_game(enemy_position: Position) -> Game {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        testing::add_ship(&mut game, 1, 0, enemy_position, 0);
+        testing::add_ship(&mut game, 0, 1, Position { x: 3, y: 4 }, 0);
+        testing::add_ship(&mut game, 0, 2, Position { x: 4, y: 5 }, 700);
+        testing::add_ship(&mut game, 0, 3, Position { x: 5, y: 5 }, 100);
+        game
+    }
+
+    fn watch(defense: &mut BaseDefense, game: &Game, turns: usize) {
+        let mut game = game.clone();
+        for _ in 0..turns {
+            defense.update_frame(&game);
+            game.turn_number += 1;
+        }
+    }
+
+    #[test]
+    fn fullest_neighbour_rams_a_ship_parked_on_the_base() {
+        let game = get_game(Position { x: 4, y: 4 });
+        let navi = Navi::new(16, 16);
+        let mut defense = BaseDefense::new();
+
+        // It might just be passing through
+        watch(&mut defense, &game, 1);
+        assert!(defense.get_intruder_on(&Position { x: 4, y: 4 }).is_some());
+        assert!(defense.plan(&game, &navi).is_empty());
+
+        watch(&mut defense, &game, 1);
+        let rams = defense.plan(&game, &navi);
+        assert_eq!(rams.len(), 1);
+        assert!(rams[0].ship_id == ShipId(2));
+        assert!(rams[0].direction == Direction::North);
+        assert_eq!(defense.get_offenses(&PlayerId(1)), 1);
+
+        // Still one offense for the same ship however long it stays
+        watch(&mut defense, &game, 3);
+        assert_eq!(defense.get_offenses(&PlayerId(1)), 1);
+    }
+
+    #[test]
+    fn emptiest_neighbour_rams_a_repeat_offender_next_to_the_base() {
+        let game = get_game(Position { x: 4, y: 6 });
+        let navi = Navi::new(16, 16);
+        let mut defense = BaseDefense::new();
+
+        watch(&mut defense, &game, 3);
+        assert!(defense.get_intruder_on(&Position { x: 4, y: 4 }).is_none());
+        assert_eq!(defense.intruders.len(), 0);
+
+        // Only ships right next to the base count
+        let mut game = get_game(Position { x: 5, y: 4 });
+        testing::add_ship(&mut game, 0, 4, Position { x: 6, y: 4 }, 50);
+        watch(&mut defense, &game, 3);
+        assert_eq!(defense.intruders.len(), 1);
+        assert_eq!(defense.get_offenses(&PlayerId(1)), 1);
+        assert!(defense.plan(&game, &navi).is_empty());
+
+        defense.offenses.insert(PlayerId(1), 2);
+        let rams = defense.plan(&game, &navi);
+        assert_eq!(rams.len(), 1);
+        assert!(rams[0].ship_id == ShipId(4));
+        assert!(rams[0].direction == Direction::West);
+    }
+}
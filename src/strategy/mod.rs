@@ -0,0 +1,120 @@
+use hlt::command::Command;
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::navi::Navi;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use strategy::analysis::BaseDistances;
+use strategy::config::Config;
+use strategy::opponents::OpponentModel;
+use strategy::ship_state::ShipRegistry;
+
+pub mod analysis;
+pub mod config;
+pub mod defense;
+pub mod opponents;
+pub mod overlord;
+pub mod recall;
+pub mod reference;
+pub mod returns;
+pub mod salvage;
+pub mod ship_state;
+pub mod spawn;
+pub mod tactics;
+pub mod targets;
+
+// Everything a strategy can lean on besides the game state itself.
+// These get refreshed once per turn before the strategy is asked for commands.
+// All tie-breaks and random choices go through rng so a seed always replays the same game.
+// The rng lives here rather than on Game: Game is rebuilt from the engine's input every turn and
+// gets copied by the forward model, and neither should reset or fork the random sequence.
+pub struct Services {
+    pub navi: Navi,
+    pub base_distances: BaseDistances,
+    pub ship_states: ShipRegistry,
+    pub opponents: OpponentModel,
+    pub rng: StdRng,
+}
+
+impl Services {
+    pub fn new(game: &Game, rng_seed: u64) -> Services {
+        Services {
+            navi: Navi::new(game.map.width, game.map.height),
+            base_distances: BaseDistances::new(game.map.width, game.map.height),
+            ship_states: ShipRegistry::new(),
+            opponents: OpponentModel::new(),
+            rng: StdRng::seed_from_u64(rng_seed),
+        }
+    }
+
+    pub fn update_frame(&mut self, game: &Game) {
+        self.navi.update_frame(game);
+        self.base_distances.update_frame(game);
+        self.ship_states.update_frame(game);
+        self.opponents.update_frame(game);
+    }
+}
+
+pub trait Strategy {
+    fn name(&self) -> &str;
+    fn play_turn(&mut self, game: &Game, services: &mut Services) -> Vec<Command>;
+}
+
+pub const DEFAULT_STRATEGY: &str = "overlord";
+
+pub fn get_strategy_names() -> Vec<&'static str> {
+    vec!["overlord", "random", "starter", "rammer", "miner"]
+}
+
+pub fn from_name(name: &str, config: Config) -> Box<dyn Strategy> {
+    match name {
+        "overlord" => Box::new(overlord::Overlord::new(config)),
+        "random" => Box::new(reference::RandomMover::new()),
+        "starter" => Box::new(reference::StarterBot::new()),
+        "rammer" => Box::new(reference::Rammer::new()),
+        "miner" => Box::new(reference::Miner::new()),
+        _ => Log::panic(&format!("Unknown strategy '{}'. Available strategies: {}.", name, get_strategy_names().join(", "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::game::Game;
+    use hlt::position::Position;
+    use strategy::config::Config;
+    use strategy::config::Mode;
+    use strategy::overlord::Overlord;
+    use super::Services;
+    use super::Strategy;
+
+    // Empty ships around the shipyard on an empty map, where every move is a tie
+    fn get_game() -> Game {
+        let shipyard = Position { x: 8, y: 8 };
+        let mut game = testing::get_game(16, &[shipyard, Position { x: 0, y: 0 }]);
+        for (id, &(dx, dy)) in [(0, 1), (1, 0), (0, -1), (-1, 0), (1, 1), (-1, -1)].iter().enumerate() {
+            testing::add_ship(&mut game, 0, id, Position { x: shipyard.x + dx, y: shipyard.y + dy }, 0);
+        }
+        game
+    }
+
+    fn play(game: &Game, rng_seed: u64) -> Vec<String> {
+        let mut services = Services::new(game, rng_seed);
+        let mut strategy = Overlord::new(Config::new(Mode::TwoPlayer));
+        services.update_frame(game);
+        strategy.play_turn(game, &mut services).into_iter().map(|command| command.0).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_moves() {
+        let game = get_game();
+        for rng_seed in 0..10 {
+            assert_eq!(play(&game, rng_seed), play(&game, rng_seed));
+        }
+        // The seed does decide the ties
+        let mut seen: Vec<Vec<String>> = (0..10).map(|rng_seed| play(&game, rng_seed)).collect();
+        seen.sort();
+        seen.dedup();
+        assert!(seen.len() > 1);
+    }
+}
@@ -0,0 +1,258 @@
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::PlayerId;
+use hlt::position::Position;
+use hlt::ShipId;
+use std::collections::HashMap;
+
+// How often we write every profile to the log
+const LOG_INTERVAL: usize = 50;
+// Enemy ships within this distance of ours count as being close to us
+const CONTACT_DISTANCE: usize = 2;
+// Collisions per hundred ship turns above which a player counts as aggressive
+const AGGRESSIVE_RAM_RATE: f64 = 0.5;
+// Ships carrying at most this much have next to nothing to lose in a collision
+const RAMMER_MAX_CARGO: usize = 100;
+
+pub struct Profile {
+    pub player_id: PlayerId,
+    pub deposits: usize,
+    pub deposited_cargo: usize,
+    pub collisions: usize,
+    pub ship_turns: usize,
+    pub spawns: usize,
+    pub last_spawn_turn: Option<usize>,
+    pub dropoff_turns: Vec<usize>,
+    pub contacts: usize,
+    pub retreats: usize,
+}
+
+impl Profile {
+    pub fn new(player_id: PlayerId) -> Profile {
+        Profile {
+            player_id,
+            deposits: 0,
+            deposited_cargo: 0,
+            collisions: 0,
+            ship_turns: 0,
+            spawns: 0,
+            last_spawn_turn: None,
+            dropoff_turns: Vec::new(),
+            contacts: 0,
+            retreats: 0,
+        }
+    }
+
+    // How full their ships usually are when they deposit
+    pub fn get_average_return_cargo(&self) -> Option<f64> {
+        if self.deposits == 0 {
+            None
+        } else {
+            Some(self.deposited_cargo as f64 / self.deposits as f64)
+        }
+    }
+
+    // Collisions per hundred ship turns
+    pub fn get_ram_rate(&self) -> f64 {
+        if self.ship_turns == 0 {
+            0.0
+        } else {
+            100.0 * self.collisions as f64 / self.ship_turns as f64
+        }
+    }
+
+    // Share of their moves near our ships that took them further away from us
+    pub fn get_avoidance(&self) -> f64 {
+        if self.contacts == 0 {
+            0.5
+        } else {
+            self.retreats as f64 / self.contacts as f64
+        }
+    }
+
+    pub fn is_aggressive(&self) -> bool {
+        self.get_ram_rate() > AGGRESSIVE_RAM_RATE
+    }
+
+    pub fn describe(&self) -> String {
+        let average_return_cargo = match self.get_average_return_cargo() {
+            Some(cargo) => format!("{:.0}", cargo),
+            None => "?".to_string(),
+        };
+        let dropoff_turns: Vec<String> = self.dropoff_turns.iter().map(|turn| turn.to_string()).collect();
+        format!(
+            "Player {}: returns with {} halite, {:.2} collisions per 100 ship turns, {} spawns (last on turn {}), dropoffs on turns [{}], avoids us {:.0}% of the time",
+            self.player_id.0, average_return_cargo, self.get_ram_rate(), self.spawns,
+            self.last_spawn_turn.map_or("-".to_string(), |turn| turn.to_string()),
+            dropoff_turns.join(", "), 100.0 * self.get_avoidance())
+    }
+}
+
+struct ShipSnapshot {
+    owner: PlayerId,
+    position: Position,
+    halite: usize,
+    // Halite on the cell under the ship
+    cell_halite: usize,
+}
+
+// Whether the wreck started the collision with the other one: it moved onto the cell the other
+// ship was on, which then got both cargos dropped on it, or it had nothing to lose and the
+// other ship was laden
+fn is_ram(game: &Game, wreck: &ShipSnapshot, other: &ShipSnapshot) -> bool {
+    if wreck.owner == other.owner {
+        return false;
+    }
+    match game.map.calculate_distance(&wreck.position, &other.position) {
+        1 if game.map.at_position(&other.position).halite > other.cell_halite => true,
+        1 | 2 => wreck.halite <= RAMMER_MAX_CARGO && other.halite > RAMMER_MAX_CARGO,
+        _ => false,
+    }
+}
+
+// Learns how each opponent plays from the frames we get, so that the tactics and navigation
+// can decide how much risk to take next to each of them.
+pub struct OpponentModel {
+    pub profiles: HashMap<PlayerId, Profile>,
+    previous_ships: HashMap<ShipId, ShipSnapshot>,
+    previous_dropoff_counts: HashMap<PlayerId, usize>,
+    has_previous_frame: bool,
+}
+
+impl OpponentModel {
+    pub fn new() -> OpponentModel {
+        OpponentModel { profiles: HashMap::new(), previous_ships: HashMap::new(), previous_dropoff_counts: HashMap::new(), has_previous_frame: false }
+    }
+
+    pub fn get_profile(&self, player_id: &PlayerId) -> Option<&Profile> {
+        self.profiles.get(player_id)
+    }
+
+    pub fn is_aggressive(&self, player_id: &PlayerId) -> bool {
+        self.get_profile(player_id).is_some_and(|profile| profile.is_aggressive())
+    }
+
+    pub fn get_avoidance(&self, player_id: &PlayerId) -> f64 {
+        self.get_profile(player_id).map_or(0.5, |profile| profile.get_avoidance())
+    }
+
+    pub fn update_frame(&mut self, game: &Game) {
+        let me = &game.players[game.my_id.0];
+        let previous_ships = &self.previous_ships;
+
+        // Ships that vanished without a dropoff showing up where they were crashed into something
+        let mut wrecks: Vec<(&ShipId, &ShipSnapshot)> = previous_ships.iter()
+            .filter(|(ship_id, _)| !game.ships.contains_key(ship_id))
+            .filter(|(_, snapshot)| !game.dropoffs.values().any(|dropoff| dropoff.position.equal(&snapshot.position)))
+            .collect();
+        wrecks.sort_by_key(|(ship_id, _)| ship_id.0);
+
+        for player in &game.players {
+            if player.id == game.my_id {
+                continue;
+            }
+            let profile = self.profiles.entry(player.id).or_insert_with(|| Profile::new(player.id));
+            profile.ship_turns += player.ship_ids.len();
+
+            // Only the collisions they started count, not the ones they were on the receiving end of
+            for (_, wreck) in &wrecks {
+                if wreck.owner == player.id && wrecks.iter().any(|(_, other)| is_ram(game, wreck, other)) {
+                    profile.collisions += 1;
+                }
+            }
+
+            let dropoff_count = player.dropoff_ids.len();
+            if dropoff_count > *self.previous_dropoff_counts.get(&player.id).unwrap_or(&0) {
+                profile.dropoff_turns.push(game.turn_number);
+            }
+            self.previous_dropoff_counts.insert(player.id, dropoff_count);
+
+            let mut bases: Vec<Position> = vec![player.shipyard.position];
+            for dropoff_id in &player.dropoff_ids {
+                bases.push(game.dropoffs[dropoff_id].position);
+            }
+
+            for ship_id in &player.ship_ids {
+                let ship = &game.ships[ship_id];
+                let previous = match previous_ships.get(ship_id) {
+                    Some(previous) => previous,
+                    None => {
+                        if self.has_previous_frame {
+                            profile.spawns += 1;
+                            profile.last_spawn_turn = Some(game.turn_number);
+                        }
+                        continue;
+                    },
+                };
+
+                if previous.halite > ship.halite && ship.halite == 0 && bases.iter().any(|base| base.equal(&ship.position)) {
+                    profile.deposits += 1;
+                    profile.deposited_cargo += previous.halite;
+                }
+
+                let previous_distance = me.ship_ids.iter()
+                    .filter_map(|my_ship_id| previous_ships.get(my_ship_id))
+                    .map(|my_ship| game.map.calculate_distance(&my_ship.position, &previous.position))
+                    .min();
+                let distance = me.ship_ids.iter()
+                    .map(|my_ship_id| game.map.calculate_distance(&game.ships[my_ship_id].position, &ship.position))
+                    .min();
+                if let (Some(previous_distance), Some(distance)) = (previous_distance, distance) {
+                    if previous_distance <= CONTACT_DISTANCE && !previous.position.equal(&ship.position) {
+                        profile.contacts += 1;
+                        if distance > previous_distance {
+                            profile.retreats += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.previous_ships = game.ships.values()
+            .map(|ship| (ship.id, ShipSnapshot { owner: ship.owner, position: ship.position, halite: ship.halite, cell_halite: game.map.at_entity(ship).halite }))
+            .collect();
+
+        self.has_previous_frame = true;
+
+        if game.turn_number.is_multiple_of(LOG_INTERVAL) {
+            let mut player_ids: Vec<&PlayerId> = self.profiles.keys().collect();
+            player_ids.sort_by_key(|player_id| player_id.0);
+            for player_id in player_ids {
+                Log::log(&self.profiles[player_id].describe());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::PlayerId;
+    use hlt::position::Position;
+    use super::OpponentModel;
+
+    #[test]
+    fn counts_only_the_player_that_started_the_collision() {
+        let shipyards = [Position { x: 0, y: 0 }, Position { x: 8, y: 0 }, Position { x: 0, y: 8 }];
+        let mut game = testing::get_game(16, &shipyards);
+        // Player 1's empty ship steps onto the cell where player 2's laden ship is mining
+        testing::add_ship(&mut game, 1, 0, Position { x: 4, y: 4 }, 0);
+        testing::add_ship(&mut game, 2, 1, Position { x: 5, y: 4 }, 600);
+        testing::add_ship(&mut game, 0, 2, Position { x: 6, y: 6 }, 0);
+        game.map.at_position_mut(&Position { x: 5, y: 4 }).halite = 100;
+
+        let mut model = OpponentModel::new();
+        model.update_frame(&game);
+
+        let mut next = game.clone();
+        next.turn_number += 1;
+        next.ships.retain(|ship_id, _| ship_id.0 == 2);
+        next.players[1].ship_ids.clear();
+        next.players[2].ship_ids.clear();
+        next.map.at_position_mut(&Position { x: 5, y: 4 }).halite = 700;
+        model.update_frame(&next);
+
+        assert_eq!(model.get_profile(&PlayerId(1)).unwrap().collisions, 1);
+        assert_eq!(model.get_profile(&PlayerId(2)).unwrap().collisions, 0);
+    }
+}
@@ -0,0 +1,418 @@
+use hlt::command::Command;
+use hlt::direction::Direction;
+use hlt::game::Game;
+use hlt::game_map::GameMap;
+use hlt::log::Log;
+use hlt::position::Position;
+use hlt::ship::Ship;
+use hlt::ShipId;
+use rand::Rng;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use strategy::config::Config;
+use strategy::defense::BaseDefense;
+use strategy::recall::RecallPlanner;
+use strategy::returns;
+use strategy::salvage::SalvageDispatcher;
+use strategy::ship_state::ShipState;
+use strategy::Services;
+use strategy::Strategy;
+use strategy::spawn::SpawnPolicy;
+use strategy::tactics;
+use strategy::tactics::Ram;
+use strategy::targets::TargetAssigner;
+
+// Ships at least this far from every base turn into a dropoff, once there are enough ships to use it
+// and the cells within DROPOFF_RADIUS hold some multiple of what it costs
+const DROPOFF_DISTANCE: usize = 15;
+const SHIPS_PER_DROPOFF: usize = 12;
+const DROPOFF_RADIUS: i32 = 4;
+const DROPOFF_HALITE_FACTOR: usize = 3;
+// Ships carrying this much get away from emptier enemy ships that might ram them
+const FLEE_HALITE: usize = 500;
+
+fn can_move(map: &GameMap, ship: &Ship) -> bool {
+  (map.at_entity(ship).halite as f64 * 0.1) <= ship.halite as f64
+}
+
+fn get_nearby_halite(game: &Game, position: &Position) -> usize {
+    let mut halite = 0;
+    for dy in -DROPOFF_RADIUS..=DROPOFF_RADIUS {
+        for dx in -DROPOFF_RADIUS..=DROPOFF_RADIUS {
+            if dx.abs() + dy.abs() <= DROPOFF_RADIUS {
+                halite += game.map.at_position(&Position { x: position.x + dx, y: position.y + dy }).halite;
+            }
+        }
+    }
+    halite
+}
+
+// The original greedy bot: mine until full, go home, repeat.
+pub struct Overlord {
+    config: Config,
+    spawn_policy: SpawnPolicy,
+    targets: TargetAssigner,
+    salvage: SalvageDispatcher,
+    defense: BaseDefense,
+    recall: RecallPlanner,
+}
+
+impl Overlord {
+    pub fn new(config: Config) -> Overlord {
+        Overlord { config, spawn_policy: SpawnPolicy::new(), targets: TargetAssigner::new(), salvage: SalvageDispatcher::new(), defense: BaseDefense::new(), recall: RecallPlanner::new() }
+    }
+
+    // Far out from every base, with enough ships to use it and halite around it, while there is
+    // enough of the game left for it to pay off
+    fn should_build_dropoff(game: &Game, services: &Services, ship: &Ship, halite: usize) -> bool {
+        let me = &game.players[game.my_id.0];
+        let cell = game.map.at_entity(ship);
+        let remaining_turns = game.constants.max_turns.saturating_sub(game.turn_number);
+        services.base_distances.get_distance(&ship.position) >= DROPOFF_DISTANCE
+            && me.ship_ids.len() >= (me.dropoff_ids.len() + 1) * SHIPS_PER_DROPOFF
+            && remaining_turns > game.constants.max_turns / 4
+            && cell.structure.is_none()
+            && halite + ship.halite + cell.halite >= game.constants.dropoff_cost
+            && get_nearby_halite(game, &ship.position) >= DROPOFF_HALITE_FACTOR * game.constants.dropoff_cost
+    }
+}
+
+impl Strategy for Overlord {
+    fn name(&self) -> &str {
+        "Overlord"
+    }
+
+    fn play_turn(&mut self, game: &Game, services: &mut Services) -> Vec<Command> {
+        self.spawn_policy.update_frame(game);
+        self.salvage.update_frame(game);
+        self.defense.update_frame(game);
+        self.recall.plan(game, &mut services.rng);
+
+        let me = &game.players[game.my_id.0];
+
+        // Every ship gets its own cargo threshold for going home
+        let mut return_thresholds: HashMap<ShipId, usize> = HashMap::new();
+        let mut returning_ships: HashSet<ShipId> = HashSet::new();
+        let mut recalled_ships: HashSet<ShipId> = HashSet::new();
+        for ship_id in &me.ship_ids {
+            let ship = &game.ships[ship_id];
+            let threshold = returns::get_return_threshold(game, &services.base_distances, ship, &self.config);
+            return_thresholds.insert(*ship_id, threshold);
+            // Ships keep heading home once they started, until they reach the base they were heading to
+            let is_home_bound = services.ship_states.get_return_base(ship_id).is_some_and(|base| !ship.position.equal(&base));
+            if self.recall.is_recalled(ship_id, game.turn_number, self.config.return_home_margin) {
+                recalled_ships.insert(*ship_id);
+                returning_ships.insert(*ship_id);
+            } else if ship.is_full() || ship.halite >= threshold || is_home_bound {
+                returning_ships.insert(*ship_id);
+            }
+        }
+
+        // Free ships close to dropped cargo go pick it up, the others get a mining target for this turn
+        let free_ship_ids: Vec<ShipId> = me.ship_ids.iter()
+            .filter(|ship_id| !returning_ships.contains(ship_id))
+            .cloned()
+            .collect();
+        let salvage_targets = self.salvage.dispatch(game, &free_ship_ids);
+        let explorer_ids: Vec<ShipId> = free_ship_ids.iter()
+            .filter(|ship_id| !salvage_targets.contains_key(ship_id))
+            .cloned()
+            .collect();
+        self.targets.assign(game, &services.base_distances, &services.navi.traffic, &explorer_ids, &return_thresholds, self.config.enemy_contact_weight);
+
+        // At most one new dropoff a turn, paid for before any ship gets built
+        let mut halite = me.halite;
+        let dropoff_builder = me.ship_ids.iter()
+            .filter(|ship_id| !recalled_ships.contains(ship_id))
+            .map(|ship_id| &game.ships[ship_id])
+            .filter(|ship| Overlord::should_build_dropoff(game, services, ship, halite))
+            .max_by_key(|ship| (services.base_distances.get_distance(&ship.position), ship.id.0))
+            .map(|ship| ship.id);
+        if let Some(ship_id) = dropoff_builder {
+            let ship = &game.ships[&ship_id];
+            halite = (halite + ship.halite + game.map.at_entity(ship).halite) - game.constants.dropoff_cost;
+        }
+
+        let navi = &mut services.navi;
+        let rng = &mut services.rng;
+        let ship_states = &mut services.ship_states;
+        let base_distances = &services.base_distances;
+        let map = &game.map;
+        let is_final_turn = RecallPlanner::is_final_turn(game);
+
+        let mut command_queue: Vec<Command> = Vec::new();
+        let mut current_positions: Vec<Position> = Vec::new();
+        let mut future_positions: Vec<Position> = Vec::new();
+        // Stay clear of the ships of players that have been ramming, or of everybody in 4 player games
+        for enemy in game.ships.values() {
+            if enemy.owner != game.my_id && (self.config.avoid_all_enemies || services.opponents.is_aggressive(&enemy.owner)) {
+                navi.mark_dangerous(&enemy.position);
+                for position in enemy.position.get_surrounding_cardinals() {
+                    navi.mark_dangerous(&position);
+                }
+            }
+        }
+
+        // Laden ships right next to an emptier ship that is out to ram, or to any enemy in 4 player games
+        let opponents = &services.opponents;
+        let avoid_all_enemies = self.config.avoid_all_enemies;
+        let threatened_ships: HashSet<ShipId> = me.ship_ids.iter()
+            .filter(|ship_id| {
+                let ship = &game.ships[ship_id];
+                ship.halite >= FLEE_HALITE && game.ships.values().any(|enemy| {
+                    enemy.owner != game.my_id && enemy.halite < ship.halite && (avoid_all_enemies || opponents.is_aggressive(&enemy.owner))
+                        && map.calculate_distance(&enemy.position, &ship.position) <= 1
+                })
+            })
+            .cloned()
+            .collect();
+
+        // Defending our bases comes before going after laden enemies
+        let defense = &self.defense;
+        let mut rams: HashMap<ShipId, Ram> = HashMap::new();
+        for ram in defense.plan(game, navi).into_iter().chain(tactics::find_rams(game, navi, &services.opponents, self.config.ram_min_enemy_halite)) {
+            let is_enemy_taken = rams.values().any(|other| other.enemy_id == ram.enemy_id);
+            if !rams.contains_key(&ram.ship_id) && !is_enemy_taken {
+                rams.insert(ram.ship_id, ram);
+            }
+        }
+
+        let mut own_ships: Vec<&Ship> = Vec::new();
+        for ship_id in &me.ship_ids {
+          own_ships.push(&game.ships[ship_id]);
+        }
+        rng.shuffle(&mut own_ships);
+        let home_bound_ships: Vec<&Ship> = own_ships.iter().filter(|ship| returning_ships.contains(&ship.id)).cloned().collect();
+        navi.traffic.update_queues(&home_bound_ships);
+        // Calculate moves first for ships going for a ram, then for stuck ships, then for loaded ships
+        // in the order they queue at a nearby base, then for ships with least amount of moves possible
+        own_ships.sort_by(|ship_a, ship_b| {
+          rams.contains_key(&ship_b.id).cmp(&rams.contains_key(&ship_a.id))
+            .then(navi.get_priority(&ship_a.id).cmp(&navi.get_priority(&ship_b.id)))
+            .then(navi.traffic.get_priority(&ship_a.id).cmp(&navi.traffic.get_priority(&ship_b.id)))
+            .then(navi.get_total_safe_moves(ship_a.position).cmp(&navi.get_total_safe_moves(ship_b.position)))
+        });
+
+        for ship in own_ships {
+            let cell = map.at_entity(ship);
+            // Recalled ships go to the base the endgame planner picked, everyone else to the closest one
+            let base = match self.recall.get_base(&ship.id) {
+                Some(base) if recalled_ships.contains(&ship.id) => base,
+                _ => base_distances.get_nearest(&ship.position),
+            };
+            let base_distance = map.calculate_distance(&ship.position, &base);
+            current_positions.push(ship.position);
+            Log::log(&format!("For ship in x: {}, y: {} | is home bound? {}", ship.position.x, ship.position.y, returning_ships.contains(&ship.id)));
+            let target = salvage_targets.get(&ship.id).cloned().or_else(|| self.targets.get_target(&ship.id));
+            let is_at_target = target.is_none_or(|target| target.equal(&ship.position));
+
+            let (command, future_position) = if dropoff_builder == Some(ship.id) {
+                Log::log(&format!("Ship {} turns into a dropoff at x: {}, y: {}", ship.id.0, ship.position.x, ship.position.y));
+                ship_states.set(ship.id, ShipState::BuildingDropoff(ship.position));
+                (ship.make_dropoff(), ship.position)
+            } else if !can_move(map, ship) {
+                Log::log(&format!("CANNOT MOVE ship in x: {}, y: {} - cargo: {}, cell: {}", ship.position.x, ship.position.y, ship.halite, cell.halite));
+                // Stuck ships mine where they are, the ones heading home carry on next turn
+                if returning_ships.contains(&ship.id) {
+                  ship_states.set(ship.id, ShipState::Returning(base));
+                } else {
+                  ship_states.set(ship.id, ShipState::Mining(ship.position));
+                }
+                (ship.stay_still(), ship.position)
+            } else if let Some(ram) = rams.get(&ship.id) {
+                let future_position = ship.position.directional_offset(ram.direction);
+                Log::log(&format!("Ram enemy ship {} at x: {}, y: {}", ram.enemy_id.0, future_position.x, future_position.y));
+                ship_states.set(ship.id, ShipState::Attacking(ram.enemy_id));
+                (ship.move_ship(ram.direction), future_position)
+            } else if returning_ships.contains(&ship.id) {
+                let base_direction = if is_final_turn && base_distance == 1 {
+                  // Nothing left to lose, whoever crashes into our own base still deposits
+                  Log::log("Final turn, crash into the base");
+                  navi.get_unsafe_moves(&ship.position, &base)[0]
+                } else if base_distance == 1 && defense.get_intruder_on(&base).is_some() {
+                  // Someone is camping on the base and isn't worth ramming yet, wait for them to leave
+                  Log::log("Wait for the base to clear");
+                  Direction::Still
+                } else {
+                  navi.better_navigate(ship, &base, &me.ship_ids, &future_positions, &current_positions, rng)
+                };
+                let future_position = ship.position.directional_offset(base_direction);
+                Log::log(&format!("Move towards base: x: {}, y: {}", future_position.x, future_position.y));
+                ship_states.set(ship.id, ShipState::Returning(base));
+                (ship.move_ship(base_direction), future_position)
+            } else if threatened_ships.contains(&ship.id) {
+                let mut refuges = ship.position.get_surrounding_cardinals();
+                rng.shuffle(&mut refuges);
+                let refuge = refuges.into_iter().find(|position| {
+                  !navi.is_dangerous(position) && navi.traffic.is_allowed(position, false) && navi.is_smart_safe(position, &ship.position, &me.ship_ids, &future_positions, &current_positions)
+                });
+                match refuge {
+                  Some(position) => {
+                    Log::log(&format!("Flee from danger to x: {}, y: {}", position.x, position.y));
+                    navi.mark_unsafe(&position, ship.id);
+                    ship_states.set(ship.id, ShipState::Fleeing(position));
+                    (ship.move_ship(ship.position.get_direction_from_position(&position)), position)
+                  },
+                  None => {
+                    Log::log("Nowhere safe to flee to, stay still");
+                    ship_states.set(ship.id, ShipState::Mining(ship.position));
+                    (ship.stay_still(), ship.position)
+                  },
+                }
+            } else if is_at_target && cell.halite > self.config.min_cell_halite && !navi.traffic.is_lane_cell(&ship.position) && navi.is_smart_safe(&ship.position, &ship.position, &me.ship_ids, &future_positions, &current_positions)  {
+                Log::log(&format!("Stay still: {}", cell.halite));
+                ship_states.set(ship.id, ShipState::Mining(ship.position));
+                (ship.stay_still(), ship.position)
+            } else if let (Some(target), false) = (target, is_at_target) {
+                // Only safe moves come back, with their cell already taken
+                let direction = navi.better_navigate(ship, &target, &me.ship_ids, &future_positions, &current_positions, rng);
+                if direction != Direction::Still {
+                  Log::log(&format!("Move towards target: x: {}, y: {}", target.x, target.y));
+                } else {
+                  Log::log("Stay still, no safe move towards target!");
+                }
+                ship_states.set(ship.id, ShipState::Exploring(target));
+                (ship.move_ship(direction), ship.position.directional_offset(direction))
+            } else {
+                let mut possible_positions = ship.position.get_surrounding_cardinals();
+                rng.shuffle(&mut possible_positions);
+                possible_positions.sort_by(|position_a, position_b| map.at_position(position_b).halite.cmp(&map.at_position(position_a).halite));
+                let best_position = possible_positions.iter().find(|position| {
+                  !navi.is_backtracking(&ship.id, position) && !navi.is_dangerous(position) && navi.traffic.is_allowed(position, false) && navi.is_smart_safe(position, &ship.position, &me.ship_ids, &future_positions, &current_positions)
+                });
+                Log::log(&format!("Number of possible_positions: {}", possible_positions.len()));
+                match best_position {
+                  Some(position) => {
+                    Log::log(&format!("Best position: {}, {}", position.x, position.y));
+                    ship_states.set(ship.id, ShipState::Exploring(*position));
+                    (ship.move_ship(ship.position.get_direction_from_position(position)), *position)
+                  },
+                  None => {
+                    Log::log("Stay still no best move!");
+                    ship_states.set(ship.id, ShipState::Mining(ship.position));
+                    (ship.stay_still(), ship.position)
+                  },
+                }
+            };
+            future_positions.push(future_position);
+            command_queue.push(command);
+        }
+        let shipyard_position = map.normalize(&me.shipyard.position);
+        let is_shipyard_empty_next_turn = !future_positions.iter().any(|position| map.normalize(position).equal(&shipyard_position));
+        Log::log(&format!("Is shipyard empty next turn? {}", is_shipyard_empty_next_turn));
+
+        if
+            halite >= game.constants.ship_cost &&
+            is_shipyard_empty_next_turn &&
+            navi.is_safe(&me.shipyard.position) &&
+            self.spawn_policy.should_spawn(game, self.config.spawn_payback_margin)
+        {
+            command_queue.push(me.shipyard.spawn());
+        }
+        command_queue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::game::Game;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use strategy::config::Config;
+    use strategy::config::Mode;
+    use strategy::ship_state::ShipState;
+    use strategy::Services;
+    use strategy::Strategy;
+    use super::Overlord;
+
+    #[test]
+    fn full_ships_head_for_the_closest_base() {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        testing::add_dropoff(&mut game, 0, 0, Position { x: 4, y: 11 });
+        testing::add_ship(&mut game, 0, 0, Position { x: 4, y: 8 }, 1000);
+        let mut services = Services::new(&game, 0);
+        services.update_frame(&game);
+
+        let commands = Overlord::new(Config::new(Mode::TwoPlayer)).play_turn(&game, &mut services);
+        assert_eq!(commands[0].0, "m 0 s");
+        assert!(services.ship_states.get(&ShipId(0)) == Some(ShipState::Returning(Position { x: 4, y: 11 })));
+    }
+
+    fn play(game: &Game, mode: Mode) -> (Vec<String>, Services) {
+        play_with(game, Config::new(mode))
+    }
+
+    fn play_with(game: &Game, config: Config) -> (Vec<String>, Services) {
+        let mut services = Services::new(game, 0);
+        services.update_frame(game);
+        let commands = Overlord::new(config).play_turn(game, &mut services);
+        (commands.into_iter().map(|command| command.0).collect(), services)
+    }
+
+    #[test]
+    fn ship_far_out_in_a_rich_spot_becomes_a_dropoff() {
+        let mut game = testing::get_game(40, &[Position { x: 10, y: 10 }, Position { x: 30, y: 30 }]);
+        for cell in game.map.cells.iter_mut().flat_map(|row| row.iter_mut()) {
+            cell.halite = 300;
+        }
+        game.players[0].halite = 3500;
+        // Fifteen cells from the shipyard, with a dozen more ships mining around it
+        testing::add_ship(&mut game, 0, 0, Position { x: 18, y: 17 }, 600);
+        for id in 1..12 {
+            testing::add_ship(&mut game, 0, id, Position { x: 10 + id as i32, y: 12 }, 0);
+        }
+
+        let (commands, services) = play(&game, Mode::TwoPlayer);
+        assert!(commands.contains(&"c 0".to_string()), "{:?}", commands);
+        assert!(services.ship_states.get(&ShipId(0)) == Some(ShipState::BuildingDropoff(Position { x: 18, y: 17 })));
+        // The dropoff takes all the halite, so no ship gets built this turn
+        assert!(!commands.contains(&"g".to_string()));
+
+        // A dozen ships per dropoff
+        game.ships.remove(&ShipId(11));
+        game.players[0].ship_ids.retain(|ship_id| ship_id.0 != 11);
+        assert!(!play(&game, Mode::TwoPlayer).0.contains(&"c 0".to_string()));
+    }
+
+    #[test]
+    fn laden_ship_flees_from_an_emptier_enemy_in_four_player_games() {
+        let shipyards = [Position { x: 4, y: 4 }, Position { x: 12, y: 4 }, Position { x: 4, y: 12 }, Position { x: 12, y: 12 }];
+        let mut game = testing::get_game(16, &shipyards);
+        game.map.at_position_mut(&Position { x: 8, y: 8 }).halite = 500;
+        testing::add_ship(&mut game, 0, 0, Position { x: 8, y: 8 }, 600);
+        testing::add_ship(&mut game, 1, 1, Position { x: 9, y: 8 }, 0);
+
+        let (commands, services) = play(&game, Mode::FourPlayer);
+        let refuge = match services.ship_states.get(&ShipId(0)) {
+            Some(ShipState::Fleeing(refuge)) => refuge,
+            _ => panic!("ship 0 didn't flee: {:?}", commands),
+        };
+        // Out of the enemy's reach
+        assert!(game.map.calculate_distance(&refuge, &Position { x: 9, y: 8 }) > 1);
+        assert!(commands[0] != "m 0 o");
+
+        // In 2 player games only players known to ram are avoided
+        let (commands, services) = play(&game, Mode::TwoPlayer);
+        assert!(services.ship_states.get(&ShipId(0)) != Some(ShipState::Fleeing(refuge)), "{:?}", commands);
+    }
+
+    #[test]
+    fn ship_without_a_target_wanders_away_from_enemies() {
+        let shipyards = [Position { x: 2, y: 2 }, Position { x: 14, y: 2 }, Position { x: 2, y: 14 }, Position { x: 14, y: 14 }];
+        let mut game = testing::get_game(16, &shipyards);
+        testing::add_ship(&mut game, 0, 0, Position { x: 8, y: 8 }, 0);
+        testing::add_ship(&mut game, 1, 1, Position { x: 10, y: 8 }, 0);
+        // The richest neighbour is within the enemy's reach, and no cell near the enemy is worth targeting
+        game.map.at_position_mut(&Position { x: 9, y: 8 }).halite = 900;
+        let mut config = Config::new(Mode::FourPlayer);
+        config.enemy_contact_weight = -1.0;
+
+        let (commands, services) = play_with(&game, config);
+        assert!(commands[0] != "m 0 e" && commands[0] != "m 0 o", "{:?}", commands);
+        match services.ship_states.get(&ShipId(0)) {
+            Some(ShipState::Exploring(position)) => assert!(!services.navi.is_dangerous(&position)),
+            _ => panic!("ship 0 didn't move: {:?}", commands),
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::map_cell::Structure;
+use hlt::player::Player;
+use hlt::position::Position;
+use hlt::ship::Ship;
+use hlt::ShipId;
+use rand::Rng;
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+// Before the final turn a base only takes one ship per turn without losing one, on the final turn
+// losing ships no longer matters so one ship from each side crashes in at once
+const ENTRIES_PER_TURN: usize = 1;
+const FINAL_TURN_ENTRIES: usize = 4;
+
+pub struct Recall {
+    pub base: Position,
+    pub path_length: usize,
+    pub arrival_turn: usize,
+    pub departure_turn: usize,
+}
+
+pub fn get_nearest_base(game: &Game, me: &Player, ship: &Ship, rng: &mut StdRng) -> Position {
+    let mut bases: Vec<Position> = vec![me.shipyard.position];
+    for dropoff_id in &me.dropoff_ids {
+        bases.push(game.dropoffs[dropoff_id].position);
+    }
+    // Shuffle first so the stable sort breaks distance ties randomly
+    rng.shuffle(&mut bases);
+    bases.sort_by_key(|base| game.map.calculate_distance(&ship.position, base));
+    bases[0]
+}
+
+// Turns needed to reach the base from every cell, going around enemy structures since
+// their owners keep ships on them
+fn get_path_lengths(game: &Game, base: &Position) -> Vec<Vec<usize>> {
+    let map = &game.map;
+    let mut lengths = vec![vec![usize::MAX; map.width]; map.height];
+    let base = map.normalize(base);
+    lengths[base.y as usize][base.x as usize] = 0;
+    let mut queue: VecDeque<Position> = VecDeque::new();
+    queue.push_back(base);
+
+    while let Some(position) = queue.pop_front() {
+        let length = lengths[position.y as usize][position.x as usize];
+        for neighbor in position.get_surrounding_cardinals() {
+            let neighbor = map.normalize(&neighbor);
+            if lengths[neighbor.y as usize][neighbor.x as usize] != usize::MAX {
+                continue;
+            }
+            let is_enemy_structure = match map.at_position(&neighbor).structure {
+                Structure::Shipyard(player_id) => player_id != game.my_id,
+                Structure::Dropoff(dropoff_id) => game.dropoffs[&dropoff_id].owner != game.my_id,
+                Structure::None => false,
+            };
+            if is_enemy_structure {
+                continue;
+            }
+            lengths[neighbor.y as usize][neighbor.x as usize] = length + 1;
+            queue.push_back(neighbor);
+        }
+    }
+    lengths
+}
+
+// Schedules the final trip home of every ship that still carries cargo. Each base hands out
+// arrival turns, the final turn first, and the ships furthest away get the latest ones, so the
+// recall is staggered and every ship is back with its cargo by the end of the game.
+pub struct RecallPlanner {
+    pub recalls: HashMap<ShipId, Recall>,
+    recalled: HashMap<ShipId, Position>,
+}
+
+impl RecallPlanner {
+    pub fn new() -> RecallPlanner {
+        RecallPlanner { recalls: HashMap::new(), recalled: HashMap::new() }
+    }
+
+    pub fn is_final_turn(game: &Game) -> bool {
+        game.turn_number >= game.constants.max_turns
+    }
+
+    pub fn plan(&mut self, game: &Game, rng: &mut StdRng) {
+        let me = &game.players[game.my_id.0];
+        let last_turn = game.constants.max_turns;
+        self.recalls.clear();
+        self.recalled.retain(|ship_id, _| game.ships.contains_key(ship_id));
+
+        let mut ships_by_base: Vec<(Position, Vec<ShipId>)> = Vec::new();
+        let mut ship_ids: Vec<ShipId> = me.ship_ids.clone();
+        ship_ids.sort_by_key(|ship_id| ship_id.0);
+        for ship_id in &ship_ids {
+            let ship = &game.ships[ship_id];
+            // Empty ships have nothing to bring back, they only need to stay out of the way
+            if ship.halite == 0 {
+                self.recalled.remove(ship_id);
+                continue;
+            }
+            // Recalled ships stick to the base they were sent to
+            let base = match self.recalled.get(ship_id) {
+                Some(base) => *base,
+                None => game.map.normalize(&get_nearest_base(game, me, ship, rng)),
+            };
+            match ships_by_base.iter_mut().find(|(other, _)| other.equal(&base)) {
+                Some((_, ship_ids)) => ship_ids.push(*ship_id),
+                None => ships_by_base.push((base, vec![*ship_id])),
+            }
+        }
+
+        for (base, ship_ids) in ships_by_base {
+            let path_lengths = get_path_lengths(game, &base);
+            let mut candidates: Vec<(ShipId, usize)> = ship_ids.iter()
+                .map(|ship_id| {
+                    let ship = &game.ships[ship_id];
+                    let position = game.map.normalize(&ship.position);
+                    let mut path_length = path_lengths[position.y as usize][position.x as usize];
+                    if path_length == usize::MAX {
+                        path_length = game.map.calculate_distance(&ship.position, &base);
+                    }
+                    // A ship that can't pay for leaving its cell loses a turn mining first
+                    if game.map.at_entity(ship).halite / game.constants.move_cost_ratio > ship.halite {
+                        path_length += 1;
+                    }
+                    (*ship_id, path_length)
+                })
+                .collect();
+            candidates.sort_by_key(|(ship_id, path_length)| (usize::MAX - path_length, ship_id.0));
+
+            // The arrival turn is the turn whose move brings the ship onto the base
+            let mut entries: HashMap<usize, usize> = HashMap::new();
+            for (ship_id, path_length) in candidates {
+                let earliest_arrival = game.turn_number + path_length.max(1) - 1;
+                let mut arrival_turn = last_turn;
+                loop {
+                    let capacity = if arrival_turn == last_turn { FINAL_TURN_ENTRIES } else { ENTRIES_PER_TURN };
+                    if arrival_turn <= earliest_arrival || *entries.get(&arrival_turn).unwrap_or(&0) < capacity {
+                        break;
+                    }
+                    arrival_turn -= 1;
+                }
+                // Running late, nothing better to do than go right now
+                let arrival_turn = arrival_turn.max(earliest_arrival);
+                *entries.entry(arrival_turn).or_insert(0) += 1;
+                let departure_turn = (arrival_turn + 1).saturating_sub(path_length);
+                self.recalls.insert(ship_id, Recall { base, path_length, arrival_turn, departure_turn });
+            }
+        }
+    }
+
+    // Once recalled a ship keeps heading home until it has deposited its cargo
+    pub fn is_recalled(&mut self, ship_id: &ShipId, turn_number: usize, margin: usize) -> bool {
+        if self.recalled.contains_key(ship_id) {
+            return true;
+        }
+        let recall = match self.recalls.get(ship_id) {
+            Some(recall) => recall,
+            None => return false,
+        };
+        if turn_number + margin < recall.departure_turn {
+            return false;
+        }
+        Log::log(&format!(
+            "Ship {} recalled to {}, {}: {} turns away, due to leave on turn {} and arrive on turn {}",
+            ship_id.0, recall.base.x, recall.base.y, recall.path_length, recall.departure_turn, recall.arrival_turn));
+        self.recalled.insert(*ship_id, recall.base);
+        true
+    }
+
+    pub fn get_base(&self, ship_id: &ShipId) -> Option<Position> {
+        self.recalls.get(ship_id).map(|recall| recall.base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use super::RecallPlanner;
+
+    #[test]
+    fn every_base_staggers_its_own_arrivals() {
+        let shipyard = Position { x: 4, y: 4 };
+        let dropoff = Position { x: 4, y: 12 };
+        let mut game = testing::get_game(16, &[shipyard, Position { x: 12, y: 12 }]);
+        testing::add_dropoff(&mut game, 0, 0, dropoff);
+        // Five ships three cells from the shipyard, one two cells from the dropoff and an empty one
+        for (id, &(x, y)) in [(4, 7), (7, 4), (1, 4), (4, 1), (6, 5), (4, 10)].iter().enumerate() {
+            testing::add_ship(&mut game, 0, id, Position { x, y }, 500);
+        }
+        testing::add_ship(&mut game, 0, 6, Position { x: 8, y: 8 }, 0);
+        let last_turn = game.constants.max_turns;
+        game.turn_number = last_turn - 10;
+
+        let mut planner = RecallPlanner::new();
+        planner.plan(&game, &mut StdRng::seed_from_u64(0));
+
+        // Four ships crash into the shipyard on the final turn, the fifth enters the turn before
+        for id in 0..4 {
+            let recall = &planner.recalls[&ShipId(id)];
+            assert!(recall.base.equal(&shipyard));
+            assert_eq!((recall.path_length, recall.arrival_turn, recall.departure_turn), (3, last_turn, last_turn - 2));
+        }
+        let recall = &planner.recalls[&ShipId(4)];
+        assert_eq!((recall.arrival_turn, recall.departure_turn), (last_turn - 1, last_turn - 3));
+        // The dropoff has its own queue
+        let recall = &planner.recalls[&ShipId(5)];
+        assert!(recall.base.equal(&dropoff));
+        assert_eq!((recall.path_length, recall.arrival_turn, recall.departure_turn), (2, last_turn, last_turn - 1));
+        assert!(planner.get_base(&ShipId(6)).is_none());
+
+        assert!(!planner.is_recalled(&ShipId(4), last_turn - 4, 0));
+        assert!(planner.is_recalled(&ShipId(4), last_turn - 5, 2));
+        // Recalled ships stay recalled
+        assert!(planner.is_recalled(&ShipId(4), last_turn - 10, 0));
+        assert!(!planner.is_recalled(&ShipId(5), last_turn - 2, 0));
+        assert!(planner.is_recalled(&ShipId(5), last_turn - 1, 0));
+    }
+
+    #[test]
+    fn path_goes_around_enemy_structures() {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 4, y: 6 }]);
+        testing::add_ship(&mut game, 0, 0, Position { x: 4, y: 7 }, 500);
+        game.turn_number = game.constants.max_turns - 10;
+        let mut planner = RecallPlanner::new();
+        planner.plan(&game, &mut StdRng::seed_from_u64(0));
+        assert_eq!(planner.recalls[&ShipId(0)].path_length, 5);
+    }
+}
@@ -0,0 +1,389 @@
+use hlt::command::Command;
+use hlt::direction::Direction;
+use hlt::game::Game;
+use hlt::map_cell::Structure;
+use hlt::position::Position;
+use hlt::ship::Ship;
+use hlt::ShipId;
+use rand::Rng;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use strategy::recall;
+use strategy::Services;
+use strategy::Strategy;
+
+// Simple opponents to measure our own strategy against, without needing other bots' binaries.
+// They only use the hlt basics: Navi's occupancy grid and naive_navigate.
+
+// The starter kits stop building ships halfway through the game
+const STARTER_LAST_SPAWN_TURN: usize = 200;
+// Rammers and miners keep spawning until this share of the game is over
+const RAMMER_SPAWN_SHARE: f64 = 0.75;
+const MINER_SPAWN_SHARE: f64 = 0.55;
+// Rammers only go after ships carrying this much within this distance, and only while carrying less themselves
+const RAMMER_MIN_TARGET_HALITE: usize = 300;
+const RAMMER_RANGE: usize = 8;
+// Cargo at which a miner heads home
+const MINER_RETURN_HALITE: usize = 900;
+// How far a miner looks for a better cell
+const MINER_SEARCH_RADIUS: i32 = 8;
+// A miner keeps digging while its cell holds at least this much
+const MINER_STAY_HALITE: usize = 100;
+// Distance from every base at which a miner turns into a dropoff, and ships needed per dropoff
+const MINER_DROPOFF_DISTANCE: usize = 14;
+const MINER_SHIPS_PER_DROPOFF: usize = 12;
+// Turns a miner gives itself on top of the trip when heading home for the end of the game
+const MINER_RECALL_MARGIN: usize = 6;
+
+fn can_move(game: &Game, ship: &Ship) -> bool {
+    game.map.at_entity(ship).halite / game.constants.move_cost_ratio <= ship.halite
+}
+
+fn is_on_base(game: &Game, ship: &Ship) -> bool {
+    match game.map.at_entity(ship).structure {
+        Structure::Shipyard(owner) => owner == ship.owner,
+        Structure::Dropoff(dropoff_id) => game.dropoffs.get(&dropoff_id).is_some_and(|dropoff| dropoff.owner == ship.owner),
+        Structure::None => false,
+    }
+}
+
+fn get_turns_left(game: &Game) -> usize {
+    game.constants.max_turns.saturating_sub(game.turn_number)
+}
+
+// Spawns when the shipyard is clear after every ship has picked its move
+fn try_spawn(game: &Game, services: &Services, last_spawn_turn: usize, commands: &mut Vec<Command>) {
+    let me = &game.players[game.my_id.0];
+    if game.turn_number <= last_spawn_turn && me.halite >= game.constants.ship_cost && services.navi.is_safe(&me.shipyard.position) {
+        commands.push(me.shipyard.spawn());
+    }
+}
+
+// Every ship goes a random way, or stays, and a ship gets built on about half the turns it can
+pub struct RandomMover {}
+
+impl RandomMover {
+    pub fn new() -> RandomMover {
+        RandomMover {}
+    }
+}
+
+impl Strategy for RandomMover {
+    fn name(&self) -> &str {
+        "RandomMover"
+    }
+
+    fn play_turn(&mut self, game: &Game, services: &mut Services) -> Vec<Command> {
+        let me = &game.players[game.my_id.0];
+        let directions = [Direction::North, Direction::East, Direction::South, Direction::West, Direction::Still];
+        let mut commands: Vec<Command> = Vec::new();
+        for ship_id in &me.ship_ids {
+            let direction = directions[services.rng.gen_range(0, directions.len())];
+            commands.push(game.ships[ship_id].move_ship(direction));
+        }
+        if me.halite >= game.constants.ship_cost && services.rng.gen_bool(0.5) {
+            commands.push(me.shipyard.spawn());
+        }
+        commands
+    }
+}
+
+// What the starter kits do once their ships bring halite home: dig until the cell runs low, then
+// step to the richest neighbor, and once full go home with naive_navigate
+pub struct StarterBot {
+    returning: HashSet<ShipId>,
+}
+
+impl StarterBot {
+    pub fn new() -> StarterBot {
+        StarterBot { returning: HashSet::new() }
+    }
+
+    fn get_move(&mut self, game: &Game, services: &mut Services, ship: &Ship) -> Direction {
+        let me = &game.players[game.my_id.0];
+        if ship.position.equal(&me.shipyard.position) {
+            self.returning.remove(&ship.id);
+        } else if ship.is_full() {
+            self.returning.insert(ship.id);
+        }
+
+        if !can_move(game, ship) {
+            Direction::Still
+        } else if self.returning.contains(&ship.id) {
+            services.navi.naive_navigate(ship, &me.shipyard.position)
+        } else if game.map.at_entity(ship).halite < game.constants.max_halite / 10 {
+            let richest = ship.position.get_surrounding_cardinals().into_iter()
+                .max_by_key(|position| game.map.at_position(position).halite)
+                .unwrap();
+            services.navi.naive_navigate(ship, &richest)
+        } else {
+            Direction::Still
+        }
+    }
+}
+
+impl Strategy for StarterBot {
+    fn name(&self) -> &str {
+        "StarterBot"
+    }
+
+    fn play_turn(&mut self, game: &Game, services: &mut Services) -> Vec<Command> {
+        let me = &game.players[game.my_id.0];
+        let mut commands: Vec<Command> = Vec::new();
+        self.returning.retain(|ship_id| game.ships.contains_key(ship_id));
+        for ship_id in &me.ship_ids {
+            let ship = &game.ships[ship_id];
+            commands.push(ship.move_ship(self.get_move(game, services, ship)));
+        }
+        try_spawn(game, services, STARTER_LAST_SPAWN_TURN, &mut commands);
+        commands
+    }
+}
+
+// Trades its ships for loaded enemy ships: goes straight for the nearest one in range, the
+// richest when several are as close, and mines like the starter bot when there is nobody to hit
+pub struct Rammer {
+    starter: StarterBot,
+}
+
+impl Rammer {
+    pub fn new() -> Rammer {
+        Rammer { starter: StarterBot::new() }
+    }
+}
+
+impl Strategy for Rammer {
+    fn name(&self) -> &str {
+        "Rammer"
+    }
+
+    fn play_turn(&mut self, game: &Game, services: &mut Services) -> Vec<Command> {
+        let me = &game.players[game.my_id.0];
+        let targets: Vec<&Ship> = game.ships.values()
+            .filter(|ship| ship.owner != game.my_id && ship.halite >= RAMMER_MIN_TARGET_HALITE)
+            .collect();
+        let mut commands: Vec<Command> = Vec::new();
+        self.starter.returning.retain(|ship_id| game.ships.contains_key(ship_id));
+
+        for ship_id in &me.ship_ids {
+            let ship = &game.ships[ship_id];
+            let target = targets.iter()
+                .filter(|target| game.map.calculate_distance(&ship.position, &target.position) <= RAMMER_RANGE)
+                .min_by_key(|target| (game.map.calculate_distance(&ship.position, &target.position), usize::MAX - target.halite, target.id.0));
+            let target = match target {
+                Some(target) if ship.halite < RAMMER_MIN_TARGET_HALITE && can_move(game, ship) => target,
+                _ => {
+                    commands.push(ship.move_ship(self.starter.get_move(game, services, ship)));
+                    continue;
+                },
+            };
+
+            // Cells with enemies on them are where we want to be, only our own ships are in the way
+            let mut direction = Direction::Still;
+            for candidate in services.navi.get_unsafe_moves(&ship.position, &target.position) {
+                let position = game.map.normalize(&ship.position.directional_offset(candidate));
+                let is_free = match services.navi.occupied[position.y as usize][position.x as usize] {
+                    Some(occupier) => game.ships.get(&occupier).is_some_and(|other| other.owner != game.my_id),
+                    None => true,
+                };
+                if is_free {
+                    services.navi.mark_unsafe(&position, ship.id);
+                    direction = candidate;
+                    break;
+                }
+            }
+            commands.push(ship.move_ship(direction));
+        }
+
+        let last_spawn_turn = (game.constants.max_turns as f64 * RAMMER_SPAWN_SHARE) as usize;
+        try_spawn(game, services, last_spawn_turn, &mut commands);
+        commands
+    }
+}
+
+// A solid mining bot: every ship heads for its own cell with the best halite for the distance,
+// comes home once nearly full, far away ships become dropoffs, and everything comes home before
+// the end, crashing into the base on the last turn
+pub struct Miner {
+    returning: HashSet<ShipId>,
+}
+
+impl Miner {
+    pub fn new() -> Miner {
+        Miner { returning: HashSet::new() }
+    }
+
+    fn get_target(&self, game: &Game, ship: &Ship, claimed: &HashSet<(i32, i32)>) -> Position {
+        let mut best = ship.position;
+        let mut best_value = game.map.at_entity(ship).halite as f64;
+        for dy in -MINER_SEARCH_RADIUS..=MINER_SEARCH_RADIUS {
+            for dx in -MINER_SEARCH_RADIUS..=MINER_SEARCH_RADIUS {
+                let position = game.map.normalize(&Position { x: ship.position.x + dx, y: ship.position.y + dy });
+                if claimed.contains(&(position.x, position.y)) || game.map.at_position(&position).structure.is_some() {
+                    continue;
+                }
+                let distance = game.map.calculate_distance(&ship.position, &position);
+                let value = game.map.at_position(&position).halite as f64 / (distance + 1) as f64;
+                if value > best_value {
+                    best = position;
+                    best_value = value;
+                }
+            }
+        }
+        best
+    }
+
+    // An empty ship on a base has to make room for the ones coming home. When the way to its target
+    // is blocked it leaves by any free side, or else trades places with a ship waiting to come in.
+    fn leave_base(&self, game: &Game, services: &mut Services, ship: &Ship, swaps: &mut HashMap<ShipId, Direction>) -> Direction {
+        let navi = &mut services.navi;
+        let base = game.map.normalize(&ship.position);
+        let mut swap: Option<(Direction, ShipId)> = None;
+        for direction in Direction::get_all_cardinals() {
+            let position = game.map.normalize(&ship.position.directional_offset(direction));
+            match navi.occupied[position.y as usize][position.x as usize] {
+                None => {
+                    navi.mark_unsafe(&position, ship.id);
+                    navi.occupied[base.y as usize][base.x as usize] = None;
+                    return direction;
+                },
+                Some(other_id) => {
+                    let is_coming_in = self.returning.contains(&other_id) && game.ships.get(&other_id).is_some_and(|other| can_move(game, other));
+                    if swap.is_none() && is_coming_in && !swaps.contains_key(&other_id) {
+                        swap = Some((direction, other_id));
+                    }
+                },
+            }
+        }
+        match swap {
+            Some((direction, other_id)) => {
+                swaps.insert(other_id, direction.invert_direction());
+                navi.mark_unsafe(&base, other_id);
+                direction
+            },
+            None => Direction::Still,
+        }
+    }
+}
+
+impl Strategy for Miner {
+    fn name(&self) -> &str {
+        "Miner"
+    }
+
+    fn play_turn(&mut self, game: &Game, services: &mut Services) -> Vec<Command> {
+        let me = &game.players[game.my_id.0];
+        let turns_left = get_turns_left(game);
+        let mut commands: Vec<Command> = Vec::new();
+        let mut claimed: HashSet<(i32, i32)> = HashSet::new();
+        let mut halite = me.halite;
+        let mut is_building = false;
+        self.returning.retain(|ship_id| game.ships.contains_key(ship_id));
+
+        let mut ship_ids = me.ship_ids.clone();
+        ship_ids.sort_by_key(|ship_id| ship_id.0);
+        let mut bases: HashMap<ShipId, Position> = HashMap::new();
+        for ship_id in &ship_ids {
+            let ship = &game.ships[ship_id];
+            let base = recall::get_nearest_base(game, me, ship, &mut services.rng);
+            let base_distance = game.map.calculate_distance(&ship.position, &base);
+            if base_distance == 0 {
+                self.returning.remove(ship_id);
+            } else if ship.halite >= MINER_RETURN_HALITE || (ship.halite > 0 && turns_left <= base_distance + MINER_RECALL_MARGIN) {
+                self.returning.insert(*ship_id);
+            }
+            bases.insert(*ship_id, base);
+        }
+
+        // Ships sitting on a base move first so the ones waiting next to it know whether they can go in
+        let mut swaps: HashMap<ShipId, Direction> = HashMap::new();
+        ship_ids.sort_by_key(|ship_id| !is_on_base(game, &game.ships[ship_id]));
+        for ship_id in &ship_ids {
+            let ship = &game.ships[ship_id];
+            let base = bases[ship_id];
+            let base_distance = game.map.calculate_distance(&ship.position, &base);
+            let cell_halite = game.map.at_entity(ship).halite;
+            let can_build = !is_building
+                && base_distance >= MINER_DROPOFF_DISTANCE
+                && me.ship_ids.len() >= (me.dropoff_ids.len() + 1) * MINER_SHIPS_PER_DROPOFF
+                && turns_left > game.constants.max_turns / 4
+                && halite + ship.halite + cell_halite >= game.constants.dropoff_cost;
+            if can_build {
+                halite = (halite + ship.halite + cell_halite) - game.constants.dropoff_cost;
+                is_building = true;
+                commands.push(ship.make_dropoff());
+                continue;
+            }
+
+            let direction = if let Some(direction) = swaps.get(ship_id) {
+                *direction
+            } else if !can_move(game, ship) {
+                Direction::Still
+            } else if self.returning.contains(ship_id) {
+                // On the last turn the base takes everyone, ships sharing it sink but their cargo counts
+                if turns_left == 0 && base_distance == 1 {
+                    services.navi.get_unsafe_moves(&ship.position, &base)[0]
+                } else {
+                    services.navi.naive_navigate(ship, &base)
+                }
+            } else if cell_halite >= MINER_STAY_HALITE {
+                Direction::Still
+            } else {
+                let target = self.get_target(game, ship, &claimed);
+                claimed.insert((target.x, target.y));
+                match services.navi.naive_navigate(ship, &target) {
+                    Direction::Still if base_distance == 0 => self.leave_base(game, services, ship, &mut swaps),
+                    direction => direction,
+                }
+            };
+            commands.push(ship.move_ship(direction));
+        }
+
+        let last_spawn_turn = (game.constants.max_turns as f64 * MINER_SPAWN_SHARE) as usize;
+        if halite >= game.constants.ship_cost {
+            try_spawn(game, services, last_spawn_turn, &mut commands);
+        }
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::runner::Match;
+    use engine::runner::MatchSettings;
+    use engine::testing;
+    use hlt::direction::Direction;
+    use hlt::position::Position;
+    use strategy::Services;
+    use strategy::Strategy;
+    use super::Miner;
+
+    #[test]
+    fn empty_ship_makes_room_on_the_shipyard() {
+        let shipyard = Position { x: 4, y: 4 };
+        let mut game = testing::get_game(16, &[shipyard, Position { x: 12, y: 12 }]);
+        testing::add_ship(&mut game, 0, 0, shipyard, 0);
+        for (id, direction) in Direction::get_all_cardinals().into_iter().enumerate() {
+            testing::add_ship(&mut game, 0, id + 1, shipyard.directional_offset(direction), 950);
+        }
+        let mut services = Services::new(&game, 1);
+        services.update_frame(&game);
+
+        let commands = Miner::new().play_turn(&game, &mut services);
+        let moves: Vec<&str> = commands.iter().map(|command| command.0.as_str()).collect();
+        assert!(!moves.contains(&"m 0 o"), "{:?}", moves);
+        // The empty ship trades places with one of the laden ships, the others wait
+        let entering = moves.iter().filter(|command| !command.starts_with("m 0") && !command.ends_with(" o")).count();
+        assert_eq!(entering, 1, "{:?}", moves);
+    }
+
+    #[test]
+    fn miner_keeps_depositing_against_the_starter_bot() {
+        let mut settings = MatchSettings::new(1, 48);
+        settings.max_turns = Some(200);
+        let commands = vec!["builtin:miner".to_string(), "builtin:starter".to_string()];
+        let result = Match::new(settings, &commands).unwrap().run();
+        let miner = result.players.iter().find(|player| player.player_id.0 == 0).unwrap();
+        assert!(miner.halite > 5000, "Miner ended with {} halite", miner.halite);
+    }
+}
@@ -0,0 +1,98 @@
+use hlt::game::Game;
+use hlt::position::Position;
+use hlt::ship::Ship;
+use strategy::analysis::BaseDistances;
+use strategy::config::Config;
+
+// How far around the ship we look for more halite to mine before heading home
+const NEARBY_RADIUS: i32 = 2;
+// Below this much halite per turn the neighborhood is not worth sticking around for
+const POOR_EXTRACTION: usize = 10;
+
+// The most halite the best cell within NEARBY_RADIUS would give us in a single turn of mining
+fn get_nearby_extraction(game: &Game, position: &Position) -> usize {
+    let mut best_halite = 0;
+    for dy in -NEARBY_RADIUS..=NEARBY_RADIUS {
+        for dx in -NEARBY_RADIUS..=NEARBY_RADIUS {
+            if dx.abs() + dy.abs() > NEARBY_RADIUS {
+                continue;
+            }
+            let cell = game.map.at_position(&Position { x: position.x + dx, y: position.y + dy });
+            best_halite = best_halite.max(cell.halite);
+        }
+    }
+    best_halite.div_ceil(game.constants.extract_ratio)
+}
+
+// How much cargo this ship should have before it goes to deposit. Ships next to a base deposit early
+// since the trip is cheap, ships far out fill up, and a rich or poor neighborhood pushes that either way.
+pub fn get_return_threshold(game: &Game, base_distances: &BaseDistances, ship: &Ship, config: &Config) -> usize {
+    let max_halite = game.constants.max_halite;
+    let base_distance = base_distances.get_distance(&ship.position);
+    let remaining_turns = game.constants.max_turns.saturating_sub(game.turn_number);
+
+    // Not enough time left to deposit and come back out again, so keep mining until the final recall
+    if remaining_turns < 3 * base_distance + config.return_home_margin {
+        return max_halite;
+    }
+
+    let near_threshold = config.near_return_halite.min(max_halite);
+    let far_distance = config.far_base_distance.max(1);
+    let distance_ratio = base_distance.min(far_distance) as f64 / far_distance as f64;
+    let threshold = near_threshold + ((max_halite - near_threshold) as f64 * distance_ratio) as usize;
+
+    let space = max_halite.saturating_sub(ship.halite);
+    let nearby_extraction = get_nearby_extraction(game, &ship.position);
+    if nearby_extraction >= space {
+        // One more turn of mining nearby fills us up
+        max_halite
+    } else if nearby_extraction < POOR_EXTRACTION {
+        // Nothing worth staying for, so head back with what we have
+        threshold.min(ship.halite.max(near_threshold))
+    } else {
+        threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::position::Position;
+    use strategy::analysis::BaseDistances;
+    use strategy::config::Config;
+    use strategy::config::Mode;
+    use super::get_return_threshold;
+
+    // A ship two cells from the shipyard with every cell holding the same halite
+    fn get_threshold(cell_halite: usize, cargo: usize, turn_number: usize) -> usize {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        for cell in game.map.cells.iter_mut().flat_map(|row| row.iter_mut()) {
+            cell.halite = cell_halite;
+        }
+        game.turn_number = turn_number;
+        testing::add_ship(&mut game, 0, 0, Position { x: 4, y: 6 }, cargo);
+        let mut base_distances = BaseDistances::new(16, 16);
+        base_distances.update_frame(&game);
+        get_return_threshold(&game, &base_distances, game.ships.values().next().unwrap(), &Config::new(Mode::TwoPlayer))
+    }
+
+    #[test]
+    fn threshold_grows_with_the_distance_and_the_neighborhood() {
+        // Two tenths of the way from 600 to full
+        assert_eq!(get_threshold(100, 300, 100), 680);
+        // Nothing around to mine, so come back with what we have
+        assert_eq!(get_threshold(0, 300, 100), 600);
+        assert_eq!(get_threshold(0, 650, 100), 650);
+        // One more turn fills the ship up
+        assert_eq!(get_threshold(4000, 300, 100), 1000);
+    }
+
+    #[test]
+    fn ships_keep_mining_when_there_is_no_time_for_another_trip() {
+        let max_turns = testing::get_constants(16).max_turns;
+        // Going back and out and back again takes three trips of two turns, plus the margin of five
+        assert_eq!(get_threshold(100, 300, max_turns - 11), 680);
+        assert_eq!(get_threshold(100, 300, max_turns - 10), 1000);
+        assert_eq!(get_threshold(0, 300, max_turns - 10), 1000);
+    }
+}
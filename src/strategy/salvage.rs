@@ -0,0 +1,187 @@
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::position::Position;
+use hlt::ShipId;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// A cell gaining at least this much in one turn had cargo dropped on it
+const MIN_DROP: usize = 100;
+// Drops further than this from every free ship are left alone
+const MAX_DISPATCH_DISTANCE: usize = 8;
+// At most this many ships get sent to the same drop
+const MAX_SHIPS_PER_SITE: usize = 3;
+// Sites are forgotten after this many turns even if nobody picked them up
+const SITE_TURNS: usize = 15;
+
+pub struct Site {
+    pub position: Position,
+    pub found_turn: usize,
+}
+
+// Notices halite dropped by collisions, either from a cell suddenly gaining halite or from a
+// laden ship disappearing, and sends the closest free ships to pick it up before anybody else.
+pub struct SalvageDispatcher {
+    previous_halite: Vec<Vec<usize>>,
+    previous_ships: HashMap<ShipId, (Position, usize)>,
+    pub sites: Vec<Site>,
+}
+
+impl SalvageDispatcher {
+    pub fn new() -> SalvageDispatcher {
+        SalvageDispatcher { previous_halite: Vec::new(), previous_ships: HashMap::new(), sites: Vec::new() }
+    }
+
+    pub fn update_frame(&mut self, game: &Game) {
+        let map = &game.map;
+
+        if !self.previous_halite.is_empty() {
+            // Where laden ships were before they vanished
+            let mut wrecks: Vec<Position> = self.previous_ships.iter()
+                .filter(|(ship_id, (_, cargo))| *cargo > 0 && !game.ships.contains_key(ship_id))
+                .map(|(_, (position, _))| *position)
+                .collect();
+            wrecks.sort_by_key(|position| (position.x, position.y));
+
+            for row in &map.cells {
+                for cell in row {
+                    let previous_halite = self.previous_halite[cell.position.y as usize][cell.position.x as usize];
+                    if cell.halite <= previous_halite || cell.structure.is_some() {
+                        continue;
+                    }
+                    let increase = cell.halite - previous_halite;
+                    let is_near_wreck = wrecks.iter().any(|wreck| map.calculate_distance(wreck, &cell.position) <= 1);
+                    if (increase >= MIN_DROP || is_near_wreck) && !self.sites.iter().any(|site| site.position.equal(&cell.position)) {
+                        Log::log(&format!("Dropped halite spotted at {}, {}: +{}", cell.position.x, cell.position.y, increase));
+                        self.sites.push(Site { position: cell.position, found_turn: game.turn_number });
+                    }
+                }
+            }
+        }
+
+        let turn_number = game.turn_number;
+        self.sites.retain(|site| {
+            turn_number - site.found_turn <= SITE_TURNS && map.at_position(&site.position).halite >= MIN_DROP / 2
+        });
+
+        self.previous_halite = map.cells.iter().map(|row| row.iter().map(|cell| cell.halite).collect()).collect();
+        self.previous_ships = game.ships.values().map(|ship| (ship.id, (ship.position, ship.halite))).collect();
+    }
+
+    // Biggest drops first, each gets the closest free ships until they can carry it all
+    pub fn dispatch(&self, game: &Game, free_ship_ids: &[ShipId]) -> HashMap<ShipId, Position> {
+        let mut sites: Vec<&Site> = self.sites.iter().collect();
+        sites.sort_by(|a, b| {
+            game.map.at_position(&b.position).halite.cmp(&game.map.at_position(&a.position).halite)
+                .then(a.position.x.cmp(&b.position.x))
+                .then(a.position.y.cmp(&b.position.y))
+        });
+
+        let mut assignments: HashMap<ShipId, Position> = HashMap::new();
+        let mut busy: HashSet<ShipId> = HashSet::new();
+        for site in sites {
+            let mut site_halite = game.map.at_position(&site.position).halite;
+            let mut candidates: Vec<ShipId> = free_ship_ids.iter()
+                .filter(|ship_id| !busy.contains(ship_id))
+                .filter(|ship_id| game.map.calculate_distance(&game.ships[ship_id].position, &site.position) <= MAX_DISPATCH_DISTANCE)
+                .cloned()
+                .collect();
+            candidates.sort_by_key(|ship_id| (game.map.calculate_distance(&game.ships[ship_id].position, &site.position), ship_id.0));
+
+            for ship_id in candidates.into_iter().take(MAX_SHIPS_PER_SITE) {
+                if site_halite == 0 {
+                    break;
+                }
+                let ship = &game.ships[&ship_id];
+                let space = game.constants.max_halite.saturating_sub(ship.halite);
+                site_halite = site_halite.saturating_sub(space);
+                busy.insert(ship_id);
+                assignments.insert(ship_id, site.position);
+                Log::log(&format!("Ship {} goes to salvage {}, {}", ship_id.0, site.position.x, site.position.y));
+            }
+        }
+        assignments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::game::Game;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use super::SalvageDispatcher;
+
+    fn get_site_positions(salvage: &SalvageDispatcher) -> Vec<(i32, i32)> {
+        salvage.sites.iter().map(|site| (site.position.x, site.position.y)).collect()
+    }
+
+    fn get_next_frame(game: &Game) -> Game {
+        let mut next = game.clone();
+        next.turn_number += 1;
+        next
+    }
+
+    #[test]
+    fn spots_big_drops_and_small_ones_next_to_a_vanished_ship() {
+        let mut game = testing::get_game(16, &[Position { x: 0, y: 0 }, Position { x: 8, y: 8 }]);
+        testing::add_ship(&mut game, 1, 0, Position { x: 4, y: 4 }, 300);
+        testing::add_ship(&mut game, 1, 1, Position { x: 12, y: 4 }, 0);
+        let mut salvage = SalvageDispatcher::new();
+        salvage.update_frame(&game);
+        assert!(salvage.sites.is_empty());
+
+        // The laden ship is gone and left a little halite next to where it was, the empty one
+        // is gone too but nothing it left behind is worth a trip
+        let mut next = get_next_frame(&game);
+        next.ships.clear();
+        next.players[1].ship_ids.clear();
+        next.map.at_position_mut(&Position { x: 4, y: 5 }).halite = 60;
+        next.map.at_position_mut(&Position { x: 12, y: 5 }).halite = 60;
+        next.map.at_position_mut(&Position { x: 2, y: 12 }).halite = 150;
+        // Deposits on structures aren't up for grabs
+        next.map.at_position_mut(&Position { x: 8, y: 8 }).halite = 500;
+        salvage.update_frame(&next);
+        assert_eq!(get_site_positions(&salvage), vec![(4, 5), (2, 12)]);
+
+        // Nothing new the turn after, and a site is dropped once it's been picked clean
+        let mut next = get_next_frame(&next);
+        next.map.at_position_mut(&Position { x: 4, y: 5 }).halite = 20;
+        salvage.update_frame(&next);
+        assert_eq!(get_site_positions(&salvage), vec![(2, 12)]);
+
+        // Or once it's too old
+        let mut next = get_next_frame(&next);
+        next.turn_number += 15;
+        salvage.update_frame(&next);
+        assert!(salvage.sites.is_empty());
+    }
+
+    #[test]
+    fn sends_the_closest_ships_until_they_can_carry_it_all() {
+        let mut game = testing::get_game(32, &[Position { x: 0, y: 0 }, Position { x: 16, y: 16 }]);
+        let mut salvage = SalvageDispatcher::new();
+        salvage.update_frame(&game);
+        let mut next = get_next_frame(&game);
+        next.map.at_position_mut(&Position { x: 10, y: 10 }).halite = 1500;
+        salvage.update_frame(&next);
+        game = next;
+
+        testing::add_ship(&mut game, 0, 0, Position { x: 10, y: 11 }, 0);
+        testing::add_ship(&mut game, 0, 1, Position { x: 12, y: 10 }, 400);
+        testing::add_ship(&mut game, 0, 2, Position { x: 7, y: 10 }, 0);
+        testing::add_ship(&mut game, 0, 3, Position { x: 13, y: 10 }, 0);
+        testing::add_ship(&mut game, 0, 4, Position { x: 1, y: 10 }, 0);
+        let ship_ids: Vec<ShipId> = (0..5).map(ShipId).collect();
+
+        // 1000 space on the closest ship, 600 on the next, the third isn't needed
+        let assignments = salvage.dispatch(&game, &ship_ids);
+        assert_eq!(assignments.len(), 2);
+        assert!(assignments[&ShipId(0)].equal(&Position { x: 10, y: 10 }));
+        assert!(assignments.contains_key(&ShipId(1)));
+
+        // Ships too far away stay out of it
+        let assignments = salvage.dispatch(&game, &[ShipId(4)]);
+        assert!(assignments.is_empty());
+    }
+}
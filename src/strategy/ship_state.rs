@@ -0,0 +1,125 @@
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::position::Position;
+use hlt::ShipId;
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ShipState {
+    Exploring(Position),
+    Mining(Position),
+    Returning(Position),
+    BuildingDropoff(Position),
+    Attacking(ShipId),
+    Fleeing(Position),
+}
+
+impl ShipState {
+    pub fn describe(&self) -> String {
+        match self {
+            ShipState::Exploring(target) => format!("Exploring({}, {})", target.x, target.y),
+            ShipState::Mining(target) => format!("Mining({}, {})", target.x, target.y),
+            ShipState::Returning(base) => format!("Returning({}, {})", base.x, base.y),
+            ShipState::BuildingDropoff(site) => format!("BuildingDropoff({}, {})", site.x, site.y),
+            ShipState::Attacking(enemy_id) => format!("Attacking(ship {})", enemy_id.0),
+            ShipState::Fleeing(refuge) => format!("Fleeing({}, {})", refuge.x, refuge.y),
+        }
+    }
+}
+
+// What each of our ships is currently up to. Survives across turns and forgets ships once they are gone.
+pub struct ShipRegistry {
+    states: HashMap<ShipId, ShipState>,
+    // This turn's transitions, as they went to the log
+    pub transitions: Vec<String>,
+}
+
+impl ShipRegistry {
+    pub fn new() -> ShipRegistry {
+        ShipRegistry { states: HashMap::new(), transitions: Vec::new() }
+    }
+
+    pub fn update_frame(&mut self, game: &Game) {
+        self.transitions.clear();
+        let mut lost_ships: Vec<ShipId> = self.states.keys()
+            .filter(|ship_id| !game.ships.contains_key(ship_id))
+            .cloned()
+            .collect();
+        lost_ships.sort_by_key(|ship_id| ship_id.0);
+
+        for ship_id in lost_ships {
+            if let Some(state) = self.states.remove(&ship_id) {
+                self.record(format!("Ship {} is gone, it was {}", ship_id.0, state.describe()));
+            }
+        }
+    }
+
+    pub fn get(&self, ship_id: &ShipId) -> Option<ShipState> {
+        self.states.get(ship_id).cloned()
+    }
+
+    pub fn set(&mut self, ship_id: ShipId, state: ShipState) {
+        let previous_state = self.states.insert(ship_id, state);
+        if previous_state != Some(state) {
+            let previous = match previous_state {
+                Some(previous_state) => previous_state.describe(),
+                None => "New".to_string(),
+            };
+            self.record(format!("Ship {}: {} -> {}", ship_id.0, previous, state.describe()));
+        }
+    }
+
+    fn record(&mut self, transition: String) {
+        Log::log(&transition);
+        self.transitions.push(transition);
+    }
+
+    pub fn get_return_base(&self, ship_id: &ShipId) -> Option<Position> {
+        match self.get(ship_id) {
+            Some(ShipState::Returning(base)) => Some(base),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use super::ShipRegistry;
+    use super::ShipState;
+
+    #[test]
+    fn every_transition_gets_logged_once() {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        testing::add_ship(&mut game, 0, 0, Position { x: 4, y: 5 }, 0);
+        testing::add_ship(&mut game, 0, 1, Position { x: 9, y: 9 }, 800);
+        let mut registry = ShipRegistry::new();
+        registry.update_frame(&game);
+
+        registry.set(ShipId(0), ShipState::Exploring(Position { x: 6, y: 6 }));
+        registry.set(ShipId(0), ShipState::Exploring(Position { x: 6, y: 6 }));
+        registry.set(ShipId(0), ShipState::Mining(Position { x: 6, y: 6 }));
+        registry.set(ShipId(1), ShipState::Fleeing(Position { x: 9, y: 10 }));
+        registry.set(ShipId(1), ShipState::Attacking(ShipId(7)));
+        registry.set(ShipId(1), ShipState::Returning(Position { x: 4, y: 4 }));
+        registry.set(ShipId(1), ShipState::BuildingDropoff(Position { x: 9, y: 10 }));
+        assert_eq!(registry.transitions, vec![
+            "Ship 0: New -> Exploring(6, 6)",
+            "Ship 0: Exploring(6, 6) -> Mining(6, 6)",
+            "Ship 1: New -> Fleeing(9, 10)",
+            "Ship 1: Fleeing(9, 10) -> Attacking(ship 7)",
+            "Ship 1: Attacking(ship 7) -> Returning(4, 4)",
+            "Ship 1: Returning(4, 4) -> BuildingDropoff(9, 10)",
+        ]);
+        assert!(registry.get_return_base(&ShipId(0)).is_none());
+
+        // The ship that became a dropoff is gone the next turn
+        game.ships.remove(&ShipId(1));
+        registry.update_frame(&game);
+        assert_eq!(registry.transitions, vec!["Ship 1 is gone, it was BuildingDropoff(9, 10)"]);
+        assert!(registry.get(&ShipId(1)).is_none());
+        assert!(registry.get(&ShipId(0)) == Some(ShipState::Mining(Position { x: 6, y: 6 })));
+    }
+}
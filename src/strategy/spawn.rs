@@ -0,0 +1,149 @@
+use hlt::game::Game;
+use hlt::log::Log;
+
+// How quickly the observed mining rate follows new observations
+const RATE_SMOOTHING: f64 = 0.1;
+// Turns a new ship needs before it is actually mining
+const SPIN_UP_TURNS: usize = 10;
+
+// Decides whether a new ship would earn back its cost before the game ends.
+pub struct SpawnPolicy {
+    map_halite: usize,
+    previous_wealth: Option<usize>,
+    previous_ship_count: usize,
+    previous_dropoff_count: usize,
+    // Halite gathered per ship per turn, smoothed over the game so far
+    mining_rate: Option<f64>,
+}
+
+impl SpawnPolicy {
+    pub fn new() -> SpawnPolicy {
+        SpawnPolicy {
+            map_halite: 0,
+            previous_wealth: None,
+            previous_ship_count: 0,
+            previous_dropoff_count: 0,
+            mining_rate: None,
+        }
+    }
+
+    pub fn update_frame(&mut self, game: &Game) {
+        let me = &game.players[game.my_id.0];
+        self.map_halite = game.map.cells.iter().flat_map(|row| row.iter()).map(|cell| cell.halite).sum();
+
+        // Wealth is banked halite plus cargo, with what we spent on ships and dropoffs added back
+        let cargo: usize = me.ship_ids.iter().map(|ship_id| game.ships[ship_id].halite).sum();
+        let wealth = me.halite + cargo;
+        let ship_count = me.ship_ids.len();
+        let dropoff_count = me.dropoff_ids.len();
+
+        if let Some(previous_wealth) = self.previous_wealth {
+            let new_ships = ship_count.saturating_sub(self.previous_ship_count);
+            let new_dropoffs = dropoff_count.saturating_sub(self.previous_dropoff_count);
+            let spent = new_ships * game.constants.ship_cost + new_dropoffs * game.constants.dropoff_cost;
+            let gained = (wealth + spent) as f64 - previous_wealth as f64;
+
+            if self.previous_ship_count > 0 {
+                // Start from the prior so the first few turns of ships leaving the base don't dominate
+                let rate = gained.max(0.0) / self.previous_ship_count as f64;
+                let mining_rate = self.mining_rate.unwrap_or_else(|| self.get_prior_rate(game));
+                self.mining_rate = Some(mining_rate + RATE_SMOOTHING * (rate - mining_rate));
+            }
+        }
+
+        self.previous_wealth = Some(wealth);
+        self.previous_ship_count = ship_count;
+        self.previous_dropoff_count = dropoff_count;
+    }
+
+    // Before we have seen any ship mine, guess from the average cell on the map
+    fn get_prior_rate(&self, game: &Game) -> f64 {
+        let cell_count = (game.map.width * game.map.height) as f64;
+        self.map_halite as f64 / cell_count / (2 * game.constants.extract_ratio) as f64
+    }
+
+    pub fn get_expected_return(&self, game: &Game) -> f64 {
+        let remaining_turns = game.constants.max_turns.saturating_sub(game.turn_number);
+        let productive_turns = remaining_turns.saturating_sub(SPIN_UP_TURNS) as f64;
+        let rate = self.mining_rate.unwrap_or_else(|| self.get_prior_rate(game));
+
+        // A new ship can't get more than its share of what is left, with every fleet on the map competing for it
+        let fleet_size: usize = game.players.iter().map(|player| player.ship_ids.len()).sum();
+        let share = self.map_halite as f64 / (fleet_size + 1) as f64;
+
+        (rate * productive_turns).min(share)
+    }
+
+    pub fn should_spawn(&self, game: &Game, payback_margin: f64) -> bool {
+        let expected_return = self.get_expected_return(game);
+        let required = game.constants.ship_cost as f64 * payback_margin;
+        Log::log(&format!(
+            "Spawn policy: expected return {:.0} vs required {:.0} (mining rate {:.1}, map halite {})",
+            expected_return, required, self.mining_rate.unwrap_or_else(|| self.get_prior_rate(game)), self.map_halite));
+        expected_return > required
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::game::Game;
+    use hlt::position::Position;
+    use super::SpawnPolicy;
+
+    fn get_game(cell_halite: usize, turn_number: usize) -> Game {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        for cell in game.map.cells.iter_mut().flat_map(|row| row.iter_mut()) {
+            cell.halite = cell_halite;
+        }
+        game.turn_number = turn_number;
+        game
+    }
+
+    #[test]
+    fn ships_that_pay_back_get_built() {
+        let game = get_game(200, 1);
+        let mut policy = SpawnPolicy::new();
+        policy.update_frame(&game);
+        // 25 halite a turn for the rest of the game is far more than a ship costs
+        let productive_turns = (game.constants.max_turns - 1 - 10) as f64;
+        assert!((policy.get_expected_return(&game) - 25.0 * productive_turns).abs() < 1e-9);
+        assert!(policy.should_spawn(&game, 1.0));
+    }
+
+    #[test]
+    fn ships_that_do_not_pay_back_are_not_built() {
+        // Too late to earn the cost back
+        let late = get_game(200, testing::get_constants(16).max_turns - 40);
+        let mut policy = SpawnPolicy::new();
+        policy.update_frame(&late);
+        assert!((policy.get_expected_return(&late) - 30.0 * 25.0).abs() < 1e-9);
+        assert!(!policy.should_spawn(&late, 1.0));
+
+        // Too little left on the map to share with another ship
+        let poor = get_game(1, 1);
+        policy.update_frame(&poor);
+        assert!(!policy.should_spawn(&poor, 1.0));
+
+        // Pays back, but not by the margin asked for
+        let game = get_game(200, 1);
+        policy.update_frame(&game);
+        assert!(policy.should_spawn(&game, 1.0));
+        assert!(!policy.should_spawn(&game, 60.0));
+    }
+
+    #[test]
+    fn observed_mining_replaces_the_prior() {
+        let mut game = get_game(200, 1);
+        testing::add_ship(&mut game, 0, 0, Position { x: 5, y: 4 }, 0);
+        testing::add_ship(&mut game, 0, 1, Position { x: 3, y: 4 }, 0);
+        let mut policy = SpawnPolicy::new();
+        policy.update_frame(&game);
+
+        // Neither ship gathered anything this turn
+        game.turn_number += 1;
+        policy.update_frame(&game);
+        let remaining_turns = (game.constants.max_turns - game.turn_number - 10) as f64;
+        assert!((policy.get_expected_return(&game) - 0.9 * 25.0 * remaining_turns).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,181 @@
+use hlt::direction::Direction;
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::navi::Navi;
+use hlt::ship::Ship;
+use hlt::ShipId;
+use std::collections::HashSet;
+use strategy::opponents::OpponentModel;
+
+// How far away ships count as being around to pick up the dropped cargo
+const RECOVERY_RADIUS: usize = 3;
+
+pub struct Ram {
+    pub ship_id: ShipId,
+    pub enemy_id: ShipId,
+    pub direction: Direction,
+    pub gain: f64,
+}
+
+fn count_ships_nearby(game: &Game, ship: &Ship, owner_is_me: bool) -> usize {
+    game.ships.values()
+        .filter(|other| other.id != ship.id && (other.owner == game.my_id) == owner_is_me)
+        .filter(|other| game.map.calculate_distance(&other.position, &ship.position) <= RECOVERY_RADIUS)
+        .count()
+}
+
+// What we expect to come out of our ship colliding with the enemy ship, in halite.
+// Both cargos end up on the cell and whoever has more ships around is likely to pick them up.
+// With two players whatever the opponent loses is as good as a gain for us, but with more
+// players trading ships only helps everyone else, so only our own balance counts.
+// Opponents that tend to back away from our ships are less likely to fight over the cargo.
+pub fn get_ram_gain(game: &Game, ship: &Ship, enemy: &Ship, opponents: &OpponentModel) -> f64 {
+    let our_nearby = count_ships_nearby(game, enemy, true).saturating_sub(1) as f64;
+    let their_nearby = count_ships_nearby(game, enemy, false) as f64 * 2.0 * (1.0 - opponents.get_avoidance(&enemy.owner));
+    if our_nearby == 0.0 || our_nearby <= their_nearby {
+        return 0.0;
+    }
+
+    let recovery_chance = our_nearby / (our_nearby + their_nearby);
+    let remaining_turns = game.constants.max_turns.saturating_sub(game.turn_number);
+    let ship_value = game.constants.ship_cost as f64 * remaining_turns as f64 / game.constants.max_turns as f64;
+    let dropped = (enemy.halite + ship.halite) as f64;
+
+    let our_gain = recovery_chance * dropped - ship.halite as f64 - ship_value;
+    let their_loss = ship_value + enemy.halite as f64 * (1.0 - recovery_chance);
+
+    if game.players.len() == 2 {
+        our_gain + their_loss
+    } else {
+        our_gain
+    }
+}
+
+// Our ships next to a laden enemy that is worth ramming, at most one of ours per enemy.
+// Enemy ships carrying less than min_enemy_halite are not worth losing a ship over.
+pub fn find_rams(game: &Game, navi: &Navi, opponents: &OpponentModel, min_enemy_halite: usize) -> Vec<Ram> {
+    let me = &game.players[game.my_id.0];
+    let mut candidates: Vec<Ram> = Vec::new();
+
+    for ship_id in &me.ship_ids {
+        let ship = &game.ships[ship_id];
+        for enemy in game.ships.values() {
+            if enemy.owner == game.my_id || enemy.halite < min_enemy_halite {
+                continue;
+            }
+            // Anything dropped on a structure goes straight to its owner
+            if game.map.at_entity(enemy).structure.is_some() {
+                continue;
+            }
+            if game.map.calculate_distance(&ship.position, &enemy.position) != 1 {
+                continue;
+            }
+
+            let gain = get_ram_gain(game, ship, enemy, opponents);
+            if gain > 0.0 {
+                let direction = navi.get_unsafe_moves(&ship.position, &enemy.position)[0];
+                candidates.push(Ram { ship_id: ship.id, enemy_id: enemy.id, direction, gain });
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| b.gain.partial_cmp(&a.gain).unwrap().then(a.ship_id.0.cmp(&b.ship_id.0)).then(a.enemy_id.0.cmp(&b.enemy_id.0)));
+    let mut used_ships: HashSet<ShipId> = HashSet::new();
+    let mut used_enemies: HashSet<ShipId> = HashSet::new();
+    let mut rams: Vec<Ram> = Vec::new();
+    for ram in candidates {
+        if used_ships.contains(&ram.ship_id) || used_enemies.contains(&ram.enemy_id) {
+            continue;
+        }
+        Log::log(&format!("Ship {} rams enemy ship {} for an expected {:.0} halite", ram.ship_id.0, ram.enemy_id.0, ram.gain));
+        used_ships.insert(ram.ship_id);
+        used_enemies.insert(ram.enemy_id);
+        rams.push(ram);
+    }
+    rams
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::direction::Direction;
+    use hlt::game::Game;
+    use hlt::navi::Navi;
+    use hlt::position::Position;
+    use hlt::ShipId;
+    use strategy::opponents::OpponentModel;
+    use super::{find_rams, get_ram_gain};
+
+    fn get_game(players: usize) -> Game {
+        let shipyards = [Position { x: 0, y: 0 }, Position { x: 16, y: 0 }, Position { x: 0, y: 16 }, Position { x: 16, y: 16 }];
+        let mut game = testing::get_game(32, &shipyards[..players]);
+        // A laden enemy with two of our ships next to it and a third one close by
+        testing::add_ship(&mut game, 1, 10, Position { x: 8, y: 8 }, 900);
+        testing::add_ship(&mut game, 0, 0, Position { x: 7, y: 8 }, 300);
+        testing::add_ship(&mut game, 0, 1, Position { x: 8, y: 9 }, 0);
+        testing::add_ship(&mut game, 0, 2, Position { x: 10, y: 9 }, 0);
+        game
+    }
+
+    fn get_ship_value(game: &Game) -> f64 {
+        let max_turns = game.constants.max_turns as f64;
+        game.constants.ship_cost as f64 * (max_turns - game.turn_number as f64) / max_turns
+    }
+
+    #[test]
+    fn gain_counts_the_opponents_loss_only_in_two_player_games() {
+        let game = get_game(2);
+        let opponents = OpponentModel::new();
+        let ship_value = get_ship_value(&game);
+        // Nobody else around to fight over the cargo, so all of it comes back to us
+        let gain = get_ram_gain(&game, &game.ships[&ShipId(1)], &game.ships[&ShipId(10)], &opponents);
+        assert!((gain - (900.0 - ship_value + ship_value)).abs() < 1e-6, "{}", gain);
+
+        let game = get_game(4);
+        let gain = get_ram_gain(&game, &game.ships[&ShipId(1)], &game.ships[&ShipId(10)], &opponents);
+        assert!((gain - (900.0 - ship_value)).abs() < 1e-6, "{}", gain);
+    }
+
+    #[test]
+    fn no_gain_without_more_of_our_ships_around() {
+        let mut game = get_game(2);
+        let opponents = OpponentModel::new();
+        // Their helpers count double against an average opponent, so two outweigh our two
+        testing::add_ship(&mut game, 1, 11, Position { x: 9, y: 7 }, 0);
+        testing::add_ship(&mut game, 1, 12, Position { x: 8, y: 6 }, 0);
+        assert!(get_ram_gain(&game, &game.ships[&ShipId(1)], &game.ships[&ShipId(10)], &opponents) == 0.0);
+
+        game.ships.remove(&ShipId(11));
+        game.ships.remove(&ShipId(12));
+        game.ships.remove(&ShipId(2));
+        game.ships.remove(&ShipId(0));
+        assert!(get_ram_gain(&game, &game.ships[&ShipId(1)], &game.ships[&ShipId(10)], &opponents) == 0.0);
+    }
+
+    #[test]
+    fn emptier_ship_rams_and_each_enemy_only_once() {
+        let mut game = get_game(2);
+        let navi = Navi::new(32, 32);
+        let opponents = OpponentModel::new();
+        testing::add_ship(&mut game, 0, 3, Position { x: 6, y: 7 }, 0);
+        testing::add_ship(&mut game, 1, 11, Position { x: 9, y: 6 }, 0);
+
+        // With a helper around the enemy ship 0 would lose part of its own cargo
+        let rams = find_rams(&game, &navi, &opponents, 500);
+        assert_eq!(rams.len(), 1);
+        assert!(rams[0].ship_id == ShipId(1));
+        assert!(rams[0].enemy_id == ShipId(10));
+        assert!(rams[0].direction == Direction::North);
+
+        // Not worth a ship below the minimum
+        assert!(find_rams(&game, &navi, &opponents, 1000).is_empty());
+    }
+
+    #[test]
+    fn enemy_on_a_structure_is_left_alone() {
+        let mut game = get_game(2);
+        let navi = Navi::new(32, 32);
+        testing::add_dropoff(&mut game, 1, 0, Position { x: 8, y: 8 });
+        assert!(find_rams(&game, &navi, &OpponentModel::new(), 500).is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+use hlt::game::Game;
+use hlt::log::Log;
+use hlt::position::Position;
+use hlt::traffic::Traffic;
+use hlt::ShipId;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use strategy::analysis::BaseDistances;
+
+// Longest stay at a single cell we consider when scoring it
+const MAX_MINING_TURNS: usize = 20;
+// A ship keeps its current target unless another cell beats it by this fraction
+const HYSTERESIS: f64 = 0.25;
+// Cells this close to an enemy ship count as contested
+const CONTACT_DISTANCE: usize = 2;
+
+// Hands out mining targets so that no two ships chase the same cell and ships
+// don't keep changing their minds from one turn to the next.
+pub struct TargetAssigner {
+    targets: HashMap<ShipId, Position>,
+}
+
+impl TargetAssigner {
+    pub fn new() -> TargetAssigner {
+        TargetAssigner { targets: HashMap::new() }
+    }
+
+    pub fn get_target(&self, ship_id: &ShipId) -> Option<Position> {
+        self.targets.get(ship_id).cloned()
+    }
+
+    // Expected halite per turn for a round trip: go to the cell, mine until the cargo reaches
+    // the threshold or the cell stops giving, then go back to the closest base.
+    pub fn score(game: &Game, base_distances: &BaseDistances, source: &Position, cargo: usize, target: &Position, cargo_threshold: usize) -> f64 {
+        let extract_ratio = game.constants.extract_ratio;
+        let mut cell_halite = game.map.at_position(target).halite;
+        let mut gathered = 0;
+        let mut mining_turns = 0;
+        while mining_turns < MAX_MINING_TURNS && cargo + gathered < cargo_threshold && cell_halite > 0 {
+            let extracted = cell_halite.div_ceil(extract_ratio).min(game.constants.max_halite - cargo - gathered);
+            if extracted == 0 {
+                break;
+            }
+            cell_halite -= extracted;
+            gathered += extracted;
+            mining_turns += 1;
+        }
+
+        if gathered == 0 {
+            return 0.0;
+        }
+
+        let travel_turns = game.map.calculate_distance(source, target);
+        let return_turns = base_distances.get_distance(target);
+        gathered as f64 / (travel_turns + mining_turns + return_turns).max(1) as f64
+    }
+
+    // Contested cells, the ones next to enemy ships, have their score scaled by 1 + enemy_contact_weight.
+    // Base lane cells are never targets, a ship mining there would block the traffic.
+    pub fn assign(&mut self, game: &Game, base_distances: &BaseDistances, traffic: &Traffic, ship_ids: &[ShipId], cargo_thresholds: &HashMap<ShipId, usize>, enemy_contact_weight: f64) {
+        // Forget targets of ships that died, went home, or whose cell has been mined out
+        self.targets.retain(|ship_id, target| ship_ids.contains(ship_id) && game.map.at_position(target).halite > 0);
+
+        let enemy_positions: Vec<Position> = game.ships.values()
+            .filter(|ship| ship.owner != game.my_id)
+            .map(|ship| ship.position)
+            .collect();
+        let contested: Vec<Vec<bool>> = game.map.cells.iter()
+            .map(|row| row.iter()
+                .map(|cell| enemy_positions.iter().any(|enemy| game.map.calculate_distance(enemy, &cell.position) <= CONTACT_DISTANCE))
+                .collect())
+            .collect();
+
+        let mut candidates: Vec<(f64, ShipId, Position)> = Vec::new();
+        for ship_id in ship_ids {
+            let ship = &game.ships[ship_id];
+            let current_target = self.get_target(ship_id);
+            let cargo_threshold = cargo_thresholds.get(ship_id).cloned().unwrap_or(game.constants.max_halite);
+            let mut ship_candidates: Vec<(f64, ShipId, Position)> = Vec::new();
+            for row in &game.map.cells {
+                for cell in row {
+                    if cell.halite == 0 || cell.structure.is_some() || traffic.is_lane_cell(&cell.position) {
+                        continue;
+                    }
+                    let mut score = TargetAssigner::score(game, base_distances, &ship.position, ship.halite, &cell.position, cargo_threshold);
+                    if contested[cell.position.y as usize][cell.position.x as usize] {
+                        score *= (1.0 + enemy_contact_weight).max(0.0);
+                    }
+                    if current_target.is_some_and(|target| target.equal(&cell.position)) {
+                        score *= 1.0 + HYSTERESIS;
+                    }
+                    if score > 0.0 {
+                        ship_candidates.push((score, *ship_id, cell.position));
+                    }
+                }
+            }
+
+            // Only the best few cells per ship can ever be picked, one more than there are ships is enough
+            ship_candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap().then(a.2.x.cmp(&b.2.x)).then(a.2.y.cmp(&b.2.y)));
+            ship_candidates.truncate(ship_ids.len() + 1);
+            candidates.extend(ship_candidates);
+        }
+
+        // Greedily hand out the best remaining ship and cell pair
+        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap().then((a.1).0.cmp(&(b.1).0)));
+        let mut assigned_ships: HashSet<ShipId> = HashSet::new();
+        let mut taken_cells: HashSet<(i32, i32)> = HashSet::new();
+        for (score, ship_id, position) in candidates {
+            if assigned_ships.contains(&ship_id) || taken_cells.contains(&(position.x, position.y)) {
+                continue;
+            }
+            assigned_ships.insert(ship_id);
+            taken_cells.insert((position.x, position.y));
+
+            let previous_target = self.targets.insert(ship_id, position);
+            if previous_target.is_none_or(|target| !target.equal(&position)) {
+                Log::log(&format!("Ship {} targets {}, {} worth {:.1} halite per turn", ship_id.0, position.x, position.y, score));
+            }
+        }
+
+        self.targets.retain(|ship_id, _| assigned_ships.contains(ship_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::testing;
+    use hlt::game::Game;
+    use hlt::position::Position;
+    use hlt::traffic::Traffic;
+    use hlt::ShipId;
+    use std::collections::HashMap;
+    use strategy::analysis::BaseDistances;
+    use super::TargetAssigner;
+
+    // Two rich cells and a richer one on the shipyard's inbound lane
+    fn get_game() -> Game {
+        let mut game = testing::get_game(16, &[Position { x: 4, y: 4 }, Position { x: 12, y: 12 }]);
+        for &(x, y, halite) in &[(4, 8, 400), (8, 4, 400), (4, 3, 1000)] {
+            game.map.at_position_mut(&Position { x, y }).halite = halite;
+        }
+        testing::add_ship(&mut game, 0, 0, Position { x: 4, y: 6 }, 0);
+        testing::add_ship(&mut game, 0, 1, Position { x: 4, y: 7 }, 0);
+        game
+    }
+
+    fn assign(game: &Game, enemy_contact_weight: f64) -> TargetAssigner {
+        let mut base_distances = BaseDistances::new(16, 16);
+        base_distances.update_frame(game);
+        let mut traffic = Traffic::new(16, 16);
+        traffic.update_frame(game);
+        let mut targets = TargetAssigner::new();
+        let thresholds: HashMap<ShipId, usize> = HashMap::new();
+        targets.assign(game, &base_distances, &traffic, &[ShipId(0), ShipId(1)], &thresholds, enemy_contact_weight);
+        targets
+    }
+
+    #[test]
+    fn score_is_halite_per_turn_of_the_round_trip() {
+        let game = get_game();
+        let mut base_distances = BaseDistances::new(16, 16);
+        base_distances.update_frame(&game);
+        // Mining 100, 75 and 57 gets past 200 in three turns, with two turns there and four back
+        let score = TargetAssigner::score(&game, &base_distances, &Position { x: 4, y: 6 }, 0, &Position { x: 4, y: 8 }, 200);
+        assert!((score - 232.0 / 9.0).abs() < 1e-9);
+        // A full ship can't take any more
+        assert_eq!(TargetAssigner::score(&game, &base_distances, &Position { x: 4, y: 6 }, 1000, &Position { x: 4, y: 8 }, 1000), 0.0);
+    }
+
+    #[test]
+    fn every_ship_gets_its_own_cell_off_the_lanes() {
+        let targets = assign(&get_game(), 0.0);
+        assert!(targets.get_target(&ShipId(1)).unwrap().equal(&Position { x: 4, y: 8 }));
+        assert!(targets.get_target(&ShipId(0)).unwrap().equal(&Position { x: 8, y: 4 }));
+    }
+
+    #[test]
+    fn contested_cells_are_scaled_by_the_contact_weight() {
+        let mut game = get_game();
+        testing::add_ship(&mut game, 1, 2, Position { x: 8, y: 5 }, 0);
+        let targets = assign(&game, -1.0);
+        assert!(targets.get_target(&ShipId(1)).unwrap().equal(&Position { x: 4, y: 8 }));
+        assert!(targets.get_target(&ShipId(0)).is_none());
+    }
+}